@@ -1,13 +1,17 @@
 //===============================================================
 
 use bevy::prelude::*;
+use serde::Deserialize;
 
 use std::{time::Duration, collections::HashMap};
 
+use crate::general::tools::load_texture_atlas;
+
 //===============================================================
 
-#[derive(Hash, PartialEq, Eq, Clone)]
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Deserialize, Default)]
 pub enum AnimationType {
+    #[default]
     Idle,
     Walk,
     _Sprint,
@@ -16,11 +20,6 @@ pub enum AnimationType {
     Fall,
     Custom (String),
 }
-impl Default for AnimationType {
-    fn default() -> Self {
-        AnimationType::Idle
-    }
-}
 
 #[derive(PartialEq)]
 pub enum FlipAnimation {
@@ -97,12 +96,12 @@ impl Animation {
         let mut to_return = vec!();
         let mut total = 0.;
 
-        for n in 0..time_steps.len() {
-            total += time_steps[n];
+        for time_step in time_steps {
+            total += time_step;
             to_return.push(total);
         }
 
-        return to_return
+        to_return
 
     }
 
@@ -152,7 +151,7 @@ impl SpriteSheetAnimation {
 
     pub fn current(&mut self) -> Option<&mut Animation> {
         let new_val = self.animations.get_mut(&self.current_animation);
-        return new_val
+        new_val
     }
 
     pub fn set_current(&mut self, new_type: AnimationType, flipped: bool, restart: bool) -> Option<(Handle<TextureAtlas>, usize)> {
@@ -167,11 +166,11 @@ impl SpriteSheetAnimation {
                     value.current_frame = 0;
                 }
 
-                return Some((value.texture_atlas.clone(), value.current_frame))
+                Some((value.texture_atlas.clone(), value.current_frame))
 
             },
             None => {
-                return None
+                None
             },
         }
     }
@@ -259,12 +258,12 @@ impl SimpleAnimation {
         let mut to_return = vec!();
         let mut total = 0.;
 
-        for n in 0..time_steps.len() {
-            total += time_steps[n];
+        for time_step in time_steps {
+            total += time_step;
             to_return.push(total);
         }
 
-        return to_return
+        to_return
 
     }
 
@@ -273,25 +272,56 @@ impl SimpleAnimation {
         self.timer.tick(delta);
     }
     pub fn get_time(&self) -> Duration {
-        return self.timer.elapsed();
+        self.timer.elapsed()
     }
     pub fn done(&self) -> bool {
-        return self.timer.finished();
+        self.timer.finished()
     }
     pub fn restart_animation(&mut self) {
         self.current_frame = 0;
     }
     pub fn current_frame(&self) -> usize {
-        return self.current_frame;
+        self.current_frame
     }
     pub fn frame_step(&self, index: usize) -> f32 {
-        return self.frame_steps[index];
+        self.frame_steps[index]
     }
     pub fn next_frame(&mut self) {
         self.current_frame += 1;
     }
     pub fn animation_type(&self) -> AnimationType {
-        return self.animation_type.clone();
+        self.animation_type.clone()
+    }
+}
+
+//==================================================================
+
+//Describes an animation in a definition asset file
+#[derive(Deserialize, Clone, Debug)]
+pub struct AnimationDefinition {
+    pub animation_type: AnimationType,
+    pub texture: String,
+    pub tile_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    pub frame_steps: Vec<f32>,
+    pub repeating: bool,
+}
+impl AnimationDefinition {
+    pub fn load_texture_atlas(&self, assets: &AssetServer, texture_atlases: &mut Assets<TextureAtlas>) -> Handle<TextureAtlas> {
+        load_texture_atlas(assets, texture_atlases,
+            &self.texture,
+            self.tile_size, self.columns, self.rows,
+        )
+    }
+
+    pub fn create_animation(&self, texture_atlas: Handle<TextureAtlas>) -> Animation {
+        Animation::with_custom_framesteps(
+            texture_atlas,
+            self.frame_steps.clone(),
+            self.frame_steps.len(),
+            self.repeating,
+        )
     }
 }
 
@@ -0,0 +1,148 @@
+//===============================================================
+
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use rodio::{OutputStream, OutputStreamHandle, Sink};
+use serde::Deserialize;
+
+use crate::animation::animation_components::AnimationType;
+
+//===============================================================
+
+pub const SOUND_CUES_PATH: &str = "audio/sounds.cues.ron";
+
+//LDtk level field holding the path of the music to play in that level
+pub const LEVEL_MUSIC_FIELD: &str = "Music";
+pub const MUSIC_CROSSFADE_TIME: f32 = 1.5;
+
+//===============================================================
+
+//Things in the game that can make a sound
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SoundCue {
+    //Weapon id
+    WeaponFired(String),
+    PlayerHurt,
+    EnemyHurt,
+    Healed,
+    PlayerDied,
+    EnemyDied,
+    ItemPickedUp,
+    Landed,
+    AnimationFinished(AnimationType),
+}
+
+fn default_volume() -> f32 {
+    1.
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SoundCueDefinition {
+    //Relative to the assets folder
+    pub path: String,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+}
+
+//Which sound each cue plays. Loaded from SOUND_CUES_PATH
+#[derive(Deserialize, TypeUuid, Clone, Debug, Default)]
+#[uuid = "9c3d7e1a-2b4f-4e8d-a6c5-1f0e9b7d3a28"]
+pub struct SoundCueDefinitions {
+    pub cues: HashMap<SoundCue, SoundCueDefinition>,
+}
+
+#[derive(Default)]
+pub struct SoundCueDefinitionsLoader;
+impl AssetLoader for SoundCueDefinitionsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let definitions: SoundCueDefinitions = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(definitions));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cues.ron"]
+    }
+}
+
+//The loaded cue definitions and the sounds they use, kept here so they stay loaded
+#[derive(Default)]
+pub struct SoundCues {
+    pub definitions: Handle<SoundCueDefinitions>,
+    pub sources: HashMap<String, Handle<AudioSource>>,
+}
+
+pub struct PlaySoundEvent(pub SoundCue);
+
+//===============================================================
+
+//Sounds are played straight through rodio so their volume can be changed while playing.
+//Stream is None when there is no audio device
+pub struct SoundOutput {
+    pub stream: Option<(OutputStream, OutputStreamHandle)>,
+}
+impl Default for SoundOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoundOutput {
+    pub fn new() -> Self {
+        let stream = match OutputStream::try_default() {
+            Ok(stream) => Some(stream),
+            Err(e) => {
+                println!("No audio output available: {}", e);
+                None
+            },
+        };
+
+        SoundOutput {
+            stream,
+        }
+    }
+
+    pub fn handle(&self) -> Option<&OutputStreamHandle> {
+        self.stream.as_ref().map(|(_, handle)| handle)
+    }
+}
+
+pub struct MusicTrack {
+    pub path: String,
+    pub sink: Sink,
+    //How far through fading in the track is, from 0 to 1
+    pub fade: f32,
+}
+
+#[derive(Default)]
+pub struct MusicPlayer {
+    pub current: Option<MusicTrack>,
+    pub fading_out: Vec<MusicTrack>,
+    //Music waiting for its file to load before it can start
+    pub pending: Option<(String, Handle<AudioSource>)>,
+    //Level whose music still needs to be looked up, once the LDtk file is loaded
+    pub level_to_check: Option<i32>,
+}
+impl MusicPlayer {
+    //The music that is playing or about to play
+    pub fn target_path(&self) -> Option<&str> {
+        match &self.pending {
+            Some((path, _)) => Some(path),
+            None => self.current.as_ref().map(|track| track.path.as_str()),
+        }
+    }
+}
+
+//===============================================================
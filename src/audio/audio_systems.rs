@@ -0,0 +1,270 @@
+//===============================================================
+
+use std::io::Cursor;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use rodio::{Decoder, Sink, Source};
+
+use super::audio_components::*;
+
+use crate::{
+    player::player_components::Player,
+    non_player::non_player_components::NonPlayer,
+    general::{tools::get_level_string_field, general_components::{HealthChangeEvent, EntityDiedEvent}},
+    weapons::weapon_components::{FireWeaponEvent, WeaponId},
+    physics::physics_components::GroundedEvent,
+    animation::animation_components::AnimationFinishedEvent,
+    world::{ItemPickedUpEvent, LevelChangedEvent},
+    settings::settings_components::Settings,
+};
+
+//===============================================================
+
+pub fn load_sound_cues(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    commands.insert_resource(SoundCues {
+        definitions: assets.load(SOUND_CUES_PATH),
+        ..Default::default()
+    });
+}
+
+//Loads every sound the cues use whenever the cue file is loaded or edited
+pub fn load_cue_sources(
+    mut definition_events: EventReader<AssetEvent<SoundCueDefinitions>>,
+    definitions: Res<Assets<SoundCueDefinitions>>,
+    mut sound_cues: ResMut<SoundCues>,
+    assets: Res<AssetServer>,
+) {
+    for event in definition_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                if let Some(loaded) = definitions.get(handle) {
+                    sound_cues.sources = loaded.cues
+                        .values()
+                        .map(|cue| (cue.path.clone(), assets.load(cue.path.as_str())))
+                        .collect();
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+//===============================================================
+
+//Turns gameplay events into sound cues
+pub fn event_sound_cues(
+    player_query: Query<Entity, With<Player>>,
+    non_player_query: Query<Entity, With<NonPlayer>>,
+    weapon_query: Query<&WeaponId>,
+
+    mut fire_weapon_event: EventReader<FireWeaponEvent>,
+    mut health_event: EventReader<HealthChangeEvent>,
+    mut died_event: EventReader<EntityDiedEvent>,
+    mut pickup_event: EventReader<ItemPickedUpEvent>,
+    mut grounded_event: EventReader<GroundedEvent>,
+    mut animation_finished_event: EventReader<AnimationFinishedEvent>,
+
+    mut play_sound_event: EventWriter<PlaySoundEvent>,
+) {
+    let is_player = |entity: Entity| player_query.get(entity).is_ok();
+    let is_non_player = |entity: Entity| non_player_query.get(entity).is_ok();
+
+    for event in fire_weapon_event.iter() {
+        if let Ok(id) = weapon_query.get(event.0) {
+            play_sound_event.send(PlaySoundEvent(SoundCue::WeaponFired(id.0.clone())));
+        }
+    }
+
+    for event in health_event.iter() {
+        if let Some(value) = event.change_type.added() {
+            if value > 0 {
+                play_sound_event.send(PlaySoundEvent(SoundCue::Healed));
+            }
+            else if value < 0 && is_player(event.entity) {
+                play_sound_event.send(PlaySoundEvent(SoundCue::PlayerHurt));
+            }
+            else if value < 0 && is_non_player(event.entity) {
+                play_sound_event.send(PlaySoundEvent(SoundCue::EnemyHurt));
+            }
+        }
+    }
+
+    for event in died_event.iter() {
+        if is_player(event.0) {
+            play_sound_event.send(PlaySoundEvent(SoundCue::PlayerDied));
+        }
+        else if is_non_player(event.0) {
+            play_sound_event.send(PlaySoundEvent(SoundCue::EnemyDied));
+        }
+    }
+
+    for _event in pickup_event.iter() {
+        play_sound_event.send(PlaySoundEvent(SoundCue::ItemPickedUp));
+    }
+
+    //Only the player, enemies land far too often
+    for event in grounded_event.iter() {
+        if is_player(event.0) {
+            play_sound_event.send(PlaySoundEvent(SoundCue::Landed));
+        }
+    }
+
+    for event in animation_finished_event.iter() {
+        play_sound_event.send(PlaySoundEvent(SoundCue::AnimationFinished(event.animation_type.clone())));
+    }
+}
+
+pub fn play_sounds(
+    mut play_sound_event: EventReader<PlaySoundEvent>,
+    output: NonSend<SoundOutput>,
+    sound_cues: Res<SoundCues>,
+    definitions: Res<Assets<SoundCueDefinitions>>,
+    audio_sources: Res<Assets<AudioSource>>,
+    settings: Res<Settings>,
+) {
+    let handle = match output.handle() {
+        Some(handle) => handle,
+        None => return,
+    };
+    let definitions = match definitions.get(&sound_cues.definitions) {
+        Some(definitions) => definitions,
+        None => return,
+    };
+
+    for event in play_sound_event.iter() {
+
+        //Cues without a sound are fine, not everything needs one
+        let cue = match definitions.cues.get(&event.0) {
+            Some(cue) => cue,
+            None => continue,
+        };
+        let source = match sound_cues.sources.get(&cue.path).and_then(|source| audio_sources.get(source)) {
+            Some(source) => source,
+            None => continue,
+        };
+
+        match Decoder::new(Cursor::new(source.clone())) {
+            Ok(decoder) => {
+                let volume = cue.volume * settings.effects_volume();
+                if let Err(e) = handle.play_raw(decoder.convert_samples().amplify(volume)) {
+                    println!("Failed to play sound {}: {}", cue.path, e);
+                }
+            },
+            Err(e) => println!("Failed to decode sound {}: {}", cue.path, e),
+        }
+    }
+}
+
+//===============================================================
+
+pub fn choose_level_music(
+    mut level_changed_event: EventReader<LevelChangedEvent>,
+    current_level: Res<LevelSelection>,
+    mut music_player: NonSendMut<MusicPlayer>,
+    world_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    assets: Res<AssetServer>,
+) {
+    for event in level_changed_event.iter() {
+        music_player.level_to_check = Some(event.0);
+    }
+    //The starting level is selected without an event
+    if current_level.is_changed() {
+        if let LevelSelection::Uid(uid) = *current_level {
+            music_player.level_to_check = Some(uid);
+        }
+    }
+
+    let level_uid = match music_player.level_to_check {
+        Some(level_uid) => level_uid,
+        None => return,
+    };
+    let ldtk_asset = match world_query.get_single().ok().and_then(|handle| ldtk_assets.get(handle)) {
+        Some(ldtk_asset) => ldtk_asset,
+        None => return,
+    };
+    music_player.level_to_check = None;
+
+    //Levels without music keep playing whatever was playing before
+    let path = match ldtk_asset.project.levels
+        .iter()
+        .find(|level| level.uid == level_uid)
+        .and_then(|level| get_level_string_field(level, LEVEL_MUSIC_FIELD))
+    {
+        Some(path) => path,
+        None => return,
+    };
+
+    if music_player.target_path() == Some(path.as_str()) {
+        return;
+    }
+
+    if let Some(current) = music_player.current.take() {
+        music_player.fading_out.push(current);
+    }
+    let handle = assets.load(path.as_str());
+    music_player.pending = Some((path, handle));
+}
+
+//Starts music once it has loaded and crossfades between tracks
+pub fn update_music(
+    mut music_player: NonSendMut<MusicPlayer>,
+    output: NonSend<SoundOutput>,
+    audio_sources: Res<Assets<AudioSource>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let handle = match output.handle() {
+        Some(handle) => handle,
+        None => return,
+    };
+
+    let pending_source = music_player.pending
+        .as_ref()
+        .and_then(|(_, source)| audio_sources.get(source));
+
+    if let Some(source) = pending_source {
+        let (path, _) = music_player.pending.take().unwrap();
+
+        let track = Sink::try_new(handle)
+            .map_err(|e| e.to_string())
+            .and_then(|sink| {
+                let decoder = Decoder::new_looped(Cursor::new(source.clone())).map_err(|e| e.to_string())?;
+                sink.set_volume(0.);
+                sink.append(decoder);
+                Ok(sink)
+            });
+
+        match track {
+            Ok(sink) => {
+                music_player.current = Some(MusicTrack {
+                    path,
+                    sink,
+                    fade: 0.,
+                });
+            },
+            Err(e) => println!("Failed to play music {}: {}", path, e),
+        }
+    }
+
+    let fade_step = time.delta_seconds() / MUSIC_CROSSFADE_TIME;
+    let volume = settings.music_volume();
+
+    if let Some(current) = &mut music_player.current {
+        current.fade = (current.fade + fade_step).min(1.);
+        current.sink.set_volume(current.fade * volume);
+    }
+
+    //Dropping a sink stops it
+    music_player.fading_out.retain_mut(|track| {
+        track.fade -= fade_step;
+        track.sink.set_volume(track.fade.max(0.) * volume);
+        track.fade > 0.
+    });
+}
+
+//===============================================================
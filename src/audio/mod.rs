@@ -0,0 +1,34 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+pub mod audio_components;
+mod audio_systems;
+
+//===============================================================
+
+//Named so it doesn't clash with bevy's AudioPlugin, which still loads the AudioSource assets
+pub struct GameAudioPlugin;
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_non_send_resource(audio_components::SoundOutput::new())
+            .insert_non_send_resource(audio_components::MusicPlayer::default())
+
+            .add_asset::<audio_components::SoundCueDefinitions>()
+            .init_asset_loader::<audio_components::SoundCueDefinitionsLoader>()
+            .add_startup_system(audio_systems::load_sound_cues)
+            .add_system(audio_systems::load_cue_sources)
+
+            .add_event::<audio_components::PlaySoundEvent>()
+            //Needs to see who died before they're despawned
+            .add_system(audio_systems::event_sound_cues.label("SoundCues").after("ChangeHealth").before("ResolveDeath"))
+            .add_system(audio_systems::play_sounds.after("SoundCues"))
+
+            .add_system(audio_systems::choose_level_music)
+            .add_system(audio_systems::update_music)
+        ;
+    }
+}
+
+//===============================================================
@@ -0,0 +1,158 @@
+//===============================================================
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{LayerInstance, EntityInstance, prelude::{TilesetDefinition, LdtkEntity}};
+use serde::Deserialize;
+
+use crate::{
+    animation::animation_components::AnimationDefinition,
+    non_player::non_player_definitions::EnemyWeapon,
+    general::tools::get_string_field,
+};
+
+//===============================================================
+
+pub const ARENA_DOOR_COLOR: Color = Color::MAROON;
+
+//===============================================================
+
+//What makes an enemy a boss. Part of its enemy definition, and the enemy's name is the boss name
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossDefinition {
+    //In the order they happen
+    pub phases: Vec<BossPhase>,
+    #[serde(default)]
+    pub reward: Option<BossReward>,
+}
+
+//Swaps out the bosses attacks and look once its health drops low enough
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossPhase {
+    //Fraction of max health, e.g. 0.5 starts the phase at half health
+    pub health_threshold: f32,
+    //Without any, the weapons from the last phase are kept
+    #[serde(default)]
+    pub weapons: Vec<EnemyWeapon>,
+    //Only replaces the animations of the same type
+    #[serde(default)]
+    pub animations: Vec<AnimationDefinition>,
+}
+
+//Given to the player when the boss is defeated, as if they had picked it up
+#[derive(Deserialize, Clone, Debug)]
+pub enum BossReward {
+    //Same names as the ItemType of pickups in LDtk
+    Item(String),
+    Weapon(String),
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Boss {
+    pub name: String,
+    pub phases: Vec<BossPhase>,
+    //How many phases have started. The enemy's own weapons and animations are phase 0
+    pub phase: usize,
+    pub reward: Option<BossReward>,
+}
+impl Boss {
+    pub fn new(name: String, definition: &BossDefinition) -> Self {
+        Boss {
+            name,
+            phases: definition.phases.clone(),
+            phase: 0,
+            reward: definition.reward.clone(),
+        }
+    }
+}
+
+//===============================================================
+
+//Entering the arena locks its doors until the boss is beaten. Beaten bosses never lock it again
+#[derive(Component, Default, Clone, Debug)]
+pub struct BossArena {
+    pub boss: String,
+    pub half_size: Vec2,
+}
+
+#[derive(Bundle, Default, Clone)]
+pub struct BossArenaBundle {
+    arena: BossArena,
+    transform: Transform,
+    global_transform: GlobalTransform,
+}
+impl LdtkEntity for BossArenaBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+
+        let boss = get_string_field(entity_instance, "Boss").unwrap_or_default();
+        if boss.is_empty() {
+            println!("Boss arena is missing the name of its boss");
+        }
+
+        BossArenaBundle {
+            arena: BossArena {
+                boss,
+                half_size: Vec2::new(entity_instance.width as f32, entity_instance.height as f32) / 2.,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+//Blocks an exit while the arena of the same boss is locked
+#[derive(Component, Default, Clone, Debug)]
+pub struct ArenaDoor {
+    pub boss: String,
+    pub size: Vec2,
+}
+
+#[derive(Bundle, Default, Clone)]
+pub struct ArenaDoorBundle {
+    door: ArenaDoor,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+impl LdtkEntity for ArenaDoorBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+
+        let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+
+        ArenaDoorBundle {
+            door: ArenaDoor {
+                boss: get_string_field(entity_instance, "Boss").unwrap_or_default(),
+                size,
+            },
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: ARENA_DOOR_COLOR,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                //Only shown while locked
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            },
+        }
+    }
+}
+
+//The boss being fought, and the arena that's locked until it's beaten
+pub struct BossFight {
+    pub arena: Entity,
+    pub boss: String,
+}
+
+//===============================================================
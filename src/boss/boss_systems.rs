@@ -0,0 +1,176 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+use super::boss_components::*;
+
+use crate::{
+    animation::animation_components::SpriteSheetAnimation,
+    general::general_components::{Health, EntityDiedEvent},
+    physics::physics_components::ColliderBundle,
+    player::player_components::Player,
+    progression::progression_components::ProgressFlags,
+    non_player::non_player_definitions::insert_enemy_weapons,
+    weapons::weapon_components::WeaponInventory,
+    world::{ItemPickedUpEvent, PlayerPickupType},
+    ui::{Popups, ShowPopup},
+};
+
+//===============================================================
+
+fn set_doors_locked(
+    door_query: &mut Query<(Entity, &ArenaDoor, &mut Visibility)>,
+    boss: &str,
+    locked: bool,
+    commands: &mut Commands,
+) {
+    for (entity, door, mut visibility) in door_query.iter_mut() {
+        if door.boss != boss {
+            continue;
+        }
+
+        if locked {
+            commands.entity(entity).insert_bundle(ColliderBundle::wall(door.size.x, door.size.y));
+        }
+        else {
+            commands.entity(entity).remove_bundle::<ColliderBundle>();
+        }
+        visibility.is_visible = locked;
+    }
+}
+
+pub fn lock_boss_arenas(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    arena_query: Query<(Entity, &BossArena, &GlobalTransform), Without<Player>>,
+    mut door_query: Query<(Entity, &ArenaDoor, &mut Visibility)>,
+    flags: Res<ProgressFlags>,
+    mut fight: ResMut<Option<BossFight>>,
+    mut commands: Commands,
+) {
+    //Retrying or starting a new game respawns the level, and the doors with it
+    if let Some(current_fight) = &*fight {
+        if arena_query.get(current_fight.arena).is_err() {
+            *fight = None;
+        }
+        return;
+    }
+
+    let player_position = match player_query.get_single() {
+        Ok(player_transform) => player_transform.translation.truncate(),
+        Err(_) => return,
+    };
+
+    for (entity, arena, arena_transform) in arena_query.iter() {
+        if flags.contains(&ProgressFlags::boss(&arena.boss)) {
+            continue;
+        }
+
+        let offset = (player_position - arena_transform.translation.truncate()).abs();
+        if offset.x > arena.half_size.x || offset.y > arena.half_size.y {
+            continue;
+        }
+
+        set_doors_locked(&mut door_query, &arena.boss, true, &mut commands);
+        *fight = Some(BossFight {
+            arena: entity,
+            boss: arena.boss.clone(),
+        });
+        return;
+    }
+}
+
+//===============================================================
+
+pub fn update_boss_phases(
+    mut boss_query: Query<(
+        Entity, &mut Boss, &Health, &mut SpriteSheetAnimation,
+        &mut Handle<TextureAtlas>, &mut TextureAtlasSprite, Option<&WeaponInventory>,
+    )>,
+    assets: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut commands: Commands,
+) {
+    for (entity, mut boss, health, mut animation, mut texture_atlas, mut sprite, inventory) in boss_query.iter_mut() {
+
+        let health_percent = health.get_health() as f32 / health.get_max_health() as f32;
+
+        //A big enough hit can start more than one phase at once
+        let mut started = Vec::new();
+        while boss.phase < boss.phases.len() && health_percent <= boss.phases[boss.phase].health_threshold {
+            started.push(boss.phases[boss.phase].clone());
+            boss.phase += 1;
+        }
+        if started.is_empty() {
+            continue;
+        }
+
+        for phase in started.iter() {
+            for definition in phase.animations.iter() {
+                let atlas_handle = definition.load_texture_atlas(&assets, &mut texture_atlases);
+                animation.add_animation(definition.animation_type.clone(), definition.create_animation(atlas_handle));
+            }
+        }
+
+        //The current animation may have just been replaced
+        let current = animation.current_animation.clone();
+        let flipped = animation.animation_flipped;
+        if let Some((handle, frame)) = animation.set_current(current, flipped, true) {
+            *texture_atlas = handle;
+            sprite.index = frame;
+        }
+
+        if let Some(phase) = started.iter().rev().find(|phase| !phase.weapons.is_empty()) {
+            if let Some(inventory) = inventory {
+                for slot in [inventory.get_slot1(), inventory.get_slot2()] {
+                    if let Some(weapon) = *slot {
+                        commands.entity(weapon).despawn_recursive();
+                    }
+                }
+            }
+            insert_enemy_weapons(&mut commands.entity(entity), &phase.weapons, &assets);
+        }
+    }
+}
+
+//===============================================================
+
+pub fn defeat_bosses(
+    boss_query: Query<&Boss>,
+    mut door_query: Query<(Entity, &ArenaDoor, &mut Visibility)>,
+    mut died_event: EventReader<EntityDiedEvent>,
+    mut pickup_event: EventWriter<ItemPickedUpEvent>,
+    mut flags: ResMut<ProgressFlags>,
+    mut fight: ResMut<Option<BossFight>>,
+    mut popup: ResMut<Popups>,
+    mut popup_state: ResMut<State<ShowPopup>>,
+    mut commands: Commands,
+) {
+    for event in died_event.iter() {
+        let boss = match boss_query.get(event.0) {
+            Ok(boss) => boss,
+            Err(_) => continue,
+        };
+
+        flags.set(ProgressFlags::boss(&boss.name));
+
+        set_doors_locked(&mut door_query, &boss.name, false, &mut commands);
+        if (*fight).as_ref().is_some_and(|fight| fight.boss == boss.name) {
+            *fight = None;
+        }
+
+        if let Some(reward) = &boss.reward {
+            let reward = match reward {
+                BossReward::Item(item) => PlayerPickupType::new(item.clone(), "".to_string()),
+                BossReward::Weapon(weapon) => PlayerPickupType::Weapon(weapon.clone()),
+            };
+
+            *popup = reward.popup();
+            if *popup_state.current() != ShowPopup::Show {
+                let _ = popup_state.set(ShowPopup::Show);
+            }
+            pickup_event.send(ItemPickedUpEvent(reward));
+        }
+    }
+}
+
+//===============================================================
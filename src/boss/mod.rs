@@ -0,0 +1,32 @@
+//===============================================================
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::RegisterLdtkObjects;
+
+use crate::general::GameState;
+
+pub mod boss_components;
+mod boss_systems;
+
+//===============================================================
+
+pub struct BossPlugin;
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource::<Option<boss_components::BossFight>>(None)
+
+            .register_ldtk_entity::<boss_components::BossArenaBundle>("BossArena")
+            .register_ldtk_entity::<boss_components::ArenaDoorBundle>("BossArenaDoor")
+
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(boss_systems::lock_boss_arenas)
+                    .with_system(boss_systems::update_boss_phases.after("ChangeHealth").before("ResolveDeath"))
+                    .with_system(boss_systems::defeat_bosses.after("ChangeHealth").before("ResolveDeath"))
+            )
+        ;
+    }
+}
+
+//===============================================================
@@ -0,0 +1,262 @@
+//===============================================================
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+
+//===============================================================
+
+pub const CONTROLS_FILE_PATH: &str = "controls.ron";
+
+//How far a stick needs to be pushed before it counts as pressed
+pub const GAMEPAD_AXIS_THRESHOLD: f32 = 0.5;
+
+//===============================================================
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlayerAction {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+
+    Jump,
+    Cling,
+    Sprint,
+
+    PrimaryAttack,
+    SecondaryAttack,
+
+    Interact,
+
+    AimLeft,
+    AimRight,
+    AimUp,
+    AimDown,
+
+    Pause,
+}
+impl PlayerAction {
+    pub const ALL: [PlayerAction; 15] = [
+        PlayerAction::MoveLeft,
+        PlayerAction::MoveRight,
+        PlayerAction::MoveUp,
+        PlayerAction::MoveDown,
+        PlayerAction::Jump,
+        PlayerAction::Cling,
+        PlayerAction::Sprint,
+        PlayerAction::PrimaryAttack,
+        PlayerAction::SecondaryAttack,
+        PlayerAction::Interact,
+        PlayerAction::AimLeft,
+        PlayerAction::AimRight,
+        PlayerAction::AimUp,
+        PlayerAction::AimDown,
+        PlayerAction::Pause,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputBinding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    //Positive is true for right/up on a stick
+    GamepadAxis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+impl InputBinding {
+    //Short name to show the player in prompts
+    pub fn display_name(&self) -> String {
+        match self {
+            InputBinding::Key(key) => format!("{:?}", key),
+            InputBinding::GamepadButton(button) => format!("{:?}", button),
+            InputBinding::GamepadAxis { axis, positive } => {
+                format!("{:?}{}", axis, if *positive { "+" } else { "-" })
+            },
+        }
+    }
+}
+
+//===============================================================
+
+//Which keys and gamepad inputs trigger each action. Loaded from and saved to CONTROLS_FILE_PATH
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActionMap {
+    pub bindings: HashMap<PlayerAction, Vec<InputBinding>>,
+}
+impl Default for ActionMap {
+    fn default() -> Self {
+        use InputBinding::*;
+
+        let left_stick_left     = GamepadAxis { axis: GamepadAxisType::LeftStickX, positive: false };
+        let left_stick_right    = GamepadAxis { axis: GamepadAxisType::LeftStickX, positive: true };
+        let left_stick_up       = GamepadAxis { axis: GamepadAxisType::LeftStickY, positive: true };
+        let left_stick_down     = GamepadAxis { axis: GamepadAxisType::LeftStickY, positive: false };
+
+        let mut bindings = HashMap::new();
+
+        bindings.insert(PlayerAction::MoveLeft,     vec![Key(KeyCode::Left),    GamepadButton(GamepadButtonType::DPadLeft),     left_stick_left]);
+        bindings.insert(PlayerAction::MoveRight,    vec![Key(KeyCode::Right),   GamepadButton(GamepadButtonType::DPadRight),    left_stick_right]);
+        bindings.insert(PlayerAction::MoveUp,       vec![Key(KeyCode::Up),      GamepadButton(GamepadButtonType::DPadUp),       left_stick_up]);
+        bindings.insert(PlayerAction::MoveDown,     vec![Key(KeyCode::Down),    GamepadButton(GamepadButtonType::DPadDown),     left_stick_down]);
+
+        bindings.insert(PlayerAction::Jump,         vec![Key(KeyCode::Space),   GamepadButton(GamepadButtonType::South)]);
+        bindings.insert(PlayerAction::Cling,        vec![Key(KeyCode::X),       GamepadButton(GamepadButtonType::LeftTrigger2)]);
+        bindings.insert(PlayerAction::Sprint,       vec![Key(KeyCode::Z),       GamepadButton(GamepadButtonType::RightTrigger2)]);
+
+        bindings.insert(PlayerAction::PrimaryAttack,    vec![Key(KeyCode::C),   GamepadButton(GamepadButtonType::West)]);
+        bindings.insert(PlayerAction::SecondaryAttack,  vec![Key(KeyCode::A),   GamepadButton(GamepadButtonType::East)]);
+
+        bindings.insert(PlayerAction::Interact,     vec![Key(KeyCode::Z),       GamepadButton(GamepadButtonType::North)]);
+
+        //Aiming follows movement by default, with the right stick as an extra option on gamepads
+        bindings.insert(PlayerAction::AimLeft,      vec![Key(KeyCode::Left),    GamepadButton(GamepadButtonType::DPadLeft),     left_stick_left,    GamepadAxis { axis: GamepadAxisType::RightStickX, positive: false }]);
+        bindings.insert(PlayerAction::AimRight,     vec![Key(KeyCode::Right),   GamepadButton(GamepadButtonType::DPadRight),    left_stick_right,   GamepadAxis { axis: GamepadAxisType::RightStickX, positive: true }]);
+        bindings.insert(PlayerAction::AimUp,        vec![Key(KeyCode::Up),      GamepadButton(GamepadButtonType::DPadUp),       left_stick_up,      GamepadAxis { axis: GamepadAxisType::RightStickY, positive: true }]);
+        bindings.insert(PlayerAction::AimDown,      vec![Key(KeyCode::Down),    GamepadButton(GamepadButtonType::DPadDown),     left_stick_down,    GamepadAxis { axis: GamepadAxisType::RightStickY, positive: false }]);
+
+        bindings.insert(PlayerAction::Pause,        vec![Key(KeyCode::Escape),  GamepadButton(GamepadButtonType::Start)]);
+
+        ActionMap {
+            bindings,
+        }
+    }
+}
+impl ActionMap {
+    pub fn get_bindings(&self, action: PlayerAction) -> &[InputBinding] {
+        match self.bindings.get(&action) {
+            Some(bindings) => bindings,
+            None => &[],
+        }
+    }
+
+    //Every input bound to the action, for telling the player what to press
+    pub fn display_bindings(&self, action: PlayerAction) -> String {
+        let names: Vec<String> = self.get_bindings(action)
+            .iter()
+            .map(|binding| binding.display_name())
+            .collect();
+
+        if names.is_empty() {
+            return "(unbound)".to_string();
+        }
+        names.join(" / ")
+    }
+
+    //Every action that can be triggered by one of the same inputs as the given action
+    pub fn get_sharing_actions(&self, action: PlayerAction) -> Vec<PlayerAction> {
+        let bindings = self.get_bindings(action);
+
+        PlayerAction::ALL
+            .iter()
+            .filter(|other| {
+                **other == action ||
+                self.get_bindings(**other).iter().any(|binding| bindings.contains(binding))
+            })
+            .copied()
+            .collect()
+    }
+
+    //Files saved before an action existed get its default bindings
+    pub fn add_missing_defaults(&mut self) {
+        let defaults = ActionMap::default();
+        for action in PlayerAction::ALL {
+            self.bindings
+                .entry(action)
+                .or_insert_with(|| defaults.get_bindings(action).to_vec());
+        }
+    }
+}
+
+//===============================================================
+
+//Where ActionInput gets updated from. External means something else (like a replay) drives it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionInputSource {
+    Devices,
+    External,
+}
+
+//The state of every action this frame. Player systems should read this instead of raw input
+#[derive(Default, Clone, Debug)]
+pub struct ActionInput {
+    pressed: HashSet<PlayerAction>,
+    just_pressed: HashSet<PlayerAction>,
+    just_released: HashSet<PlayerAction>,
+    //Actions that were used up by something else and are ignored until released
+    consumed: HashSet<PlayerAction>,
+}
+impl ActionInput {
+    //Replaces the held actions and works out which ones changed since the last update
+    pub fn update(&mut self, mut pressed: HashSet<PlayerAction>) {
+        self.consumed.retain(|action| pressed.contains(action));
+        pressed.retain(|action| !self.consumed.contains(action));
+
+        self.just_pressed = pressed.difference(&self.pressed).copied().collect();
+        self.just_released = self.pressed.difference(&pressed).copied().collect();
+        self.pressed = pressed;
+    }
+
+    pub fn pressed(&self, action: PlayerAction) -> bool {
+        self.pressed.contains(&action)
+    }
+    pub fn just_pressed(&self, action: PlayerAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+    pub fn just_released(&self, action: PlayerAction) -> bool {
+        self.just_released.contains(&action)
+    }
+    pub fn any_just_pressed(&self) -> bool {
+        !self.just_pressed.is_empty()
+    }
+
+    pub fn get_pressed(&self) -> &HashSet<PlayerAction> {
+        &self.pressed
+    }
+
+    //Uses up an action so nothing else reacts to it, including other actions bound to the same inputs.
+    //They stay suppressed until the inputs are released
+    pub fn consume(&mut self, action: PlayerAction, action_map: &ActionMap) {
+        for sharing in action_map.get_sharing_actions(action) {
+            self.pressed.remove(&sharing);
+            self.just_pressed.remove(&sharing);
+            self.just_released.remove(&sharing);
+            self.consumed.insert(sharing);
+        }
+    }
+
+    //Uses up everything currently held, until it is released
+    pub fn consume_all(&mut self) {
+        self.consumed.extend(self.pressed.drain());
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+
+    //Drops every action except the given ones for this frame, like when a menu is open
+    pub fn retain_only(&mut self, actions: &[PlayerAction]) {
+        self.pressed.retain(|action| actions.contains(action));
+        self.just_pressed.retain(|action| actions.contains(action));
+        self.just_released.retain(|action| actions.contains(action));
+    }
+
+    fn axis(&self, left: PlayerAction, right: PlayerAction, up: PlayerAction, down: PlayerAction) -> Vec2 {
+        let mut dir = Vec2::ZERO;
+        if self.pressed(right)  { dir.x += 1.; }
+        if self.pressed(left)   { dir.x -= 1.; }
+        if self.pressed(up)     { dir.y += 1.; }
+        if self.pressed(down)   { dir.y -= 1.; }
+        dir
+    }
+
+    pub fn move_dir(&self) -> Vec2 {
+        self.axis(PlayerAction::MoveLeft, PlayerAction::MoveRight, PlayerAction::MoveUp, PlayerAction::MoveDown)
+    }
+    pub fn aim_dir(&self) -> Vec2 {
+        self.axis(PlayerAction::AimLeft, PlayerAction::AimRight, PlayerAction::AimUp, PlayerAction::AimDown)
+    }
+}
+
+//===============================================================
@@ -0,0 +1,60 @@
+//===============================================================
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use super::controls_components::*;
+
+use crate::general::tools::load_ron_or_default;
+
+//===============================================================
+
+pub fn load_controls(
+    mut action_map: ResMut<ActionMap>,
+) {
+    *action_map = load_ron_or_default(CONTROLS_FILE_PATH);
+    action_map.add_missing_defaults();
+}
+
+//===============================================================
+
+pub fn update_action_input(
+    mut action_input: ResMut<ActionInput>,
+    input_source: Res<ActionInputSource>,
+    action_map: Res<ActionMap>,
+    key_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    button_input: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+) {
+    if *input_source != ActionInputSource::Devices {
+        return;
+    }
+
+    let binding_pressed = |binding: &InputBinding| {
+        match *binding {
+            InputBinding::Key(key) => key_input.pressed(key),
+            InputBinding::GamepadButton(button_type) => {
+                gamepads.iter().any(|gamepad| button_input.pressed(GamepadButton(*gamepad, button_type)))
+            },
+            InputBinding::GamepadAxis { axis, positive } => {
+                gamepads.iter().any(|gamepad| {
+                    let value = axes.get(GamepadAxis(*gamepad, axis)).unwrap_or(0.);
+                    if positive { value > GAMEPAD_AXIS_THRESHOLD }
+                    else        { value < -GAMEPAD_AXIS_THRESHOLD }
+                })
+            },
+        }
+    };
+
+    let pressed: HashSet<PlayerAction> = PlayerAction::ALL
+        .iter()
+        .filter(|action| action_map.get_bindings(**action).iter().any(binding_pressed))
+        .copied()
+        .collect();
+
+    action_input.update(pressed);
+}
+
+//===============================================================
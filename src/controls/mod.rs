@@ -0,0 +1,27 @@
+//===============================================================
+
+use bevy::{prelude::*, input::InputSystem};
+
+pub mod controls_components;
+mod controls_systems;
+
+//===============================================================
+
+pub struct ControlsPlugin;
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(controls_components::ActionMap::default())
+            .insert_resource(controls_components::ActionInput::default())
+            .insert_resource(controls_components::ActionInputSource::Devices)
+            .add_startup_system(controls_systems::load_controls)
+
+            .add_system_to_stage(CoreStage::PreUpdate, controls_systems::update_action_input
+                .label("UpdateActionInput")
+                .after(InputSystem)
+            )
+        ;
+    }
+}
+
+//===============================================================
@@ -1,7 +1,11 @@
 
-use std::time::Duration;
+use std::{time::Duration, collections::HashMap};
 
 use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::physics::physics_components::MaxVelocity;
 
 //================================================================
 
@@ -10,6 +14,36 @@ pub struct GameCamera;
 
 //================================================================
 
+//How much game time a frame of gameplay covers. Follows the frame rate unless fixed,
+//which replays and headless apps do so the same input always plays out the same way
+#[derive(Default)]
+pub struct FrameDelta {
+    delta: Duration,
+    fixed: Option<Duration>,
+}
+impl FrameDelta {
+    pub fn fixed(step: Duration) -> Self {
+        FrameDelta {
+            delta: step,
+            fixed: Some(step),
+        }
+    }
+
+    pub fn update(&mut self, time: &Time) {
+        self.delta = self.fixed.unwrap_or_else(|| time.delta());
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+}
+
+//================================================================
+
 #[derive(Component)]
 pub struct FadeInOut {
     pub timer: Timer,
@@ -47,10 +81,10 @@ impl Health {
     }
 
     pub fn get_health(&self) -> i32 {
-        return self.current_health;
+        self.current_health
     }
     pub fn get_max_health(&self) -> i32 {
-        return self.max_health;
+        self.max_health
     }
     pub fn add_health(&mut self, to_add: i32) {
         if self.invincible {
@@ -65,6 +99,10 @@ impl Health {
             self.iframes.reset();
         }
     }
+    //Used by damage over time, which would otherwise keep the entity invincible
+    pub fn drain(&mut self, amount: i32) {
+        self.current_health = (self.current_health - amount).min(self.max_health);
+    }
     pub fn set_health(&mut self, to_set: i32) {
         self.current_health = to_set.min(self.max_health);
     }
@@ -76,18 +114,184 @@ impl Health {
         }
     }
     pub fn invincible(&self) -> bool {
-        return self.invincible;
+        self.invincible
+    }
+    pub fn iframe_time(&self) -> f32 {
+        self.iframes.duration().as_secs_f32()
     }
 }
 
 pub enum HealthChangeType{
     Set { value: i32,},
     Add { value: i32,},
+    //Reduced by resistances, and can leave a status effect behind
+    Damage (Damage),
+    //Damage over time from status effects. Ignores iframes and doesn't count as a hit
+    Drain { value: i32,},
+}
+impl HealthChangeType {
+    //How much health the change is meant to add, before resistances
+    pub fn added(&self) -> Option<i32> {
+        match self {
+            HealthChangeType::Set { .. }        => None,
+            HealthChangeType::Add { value }     => Some(*value),
+            HealthChangeType::Damage(damage)    => Some(-damage.amount),
+            HealthChangeType::Drain { .. }      => None,
+        }
+    }
 }
 
 pub struct HealthChangeEvent{
     pub entity: Entity,
     pub change_type: HealthChangeType,
+    pub knockback: Option<Knockback>,
+}
+
+//================================================================
+
+pub const HIT_STUN_TIME: f32 = 0.3;
+//How much of a knockback goes upwards, so grounded entities are lifted off the floor
+pub const KNOCKBACK_LIFT: f32 = 0.5;
+
+//Pushes whatever was hit away from where the hit came from
+#[derive(Clone, Copy, Debug)]
+pub struct Knockback {
+    pub source: Vec2,
+    pub force: f32,
+}
+impl Knockback {
+    pub fn impulse(&self, position: Vec2) -> Vec2 {
+        let away = if position.x >= self.source.x { 1. } else { -1. };
+        Vec2::new(away, KNOCKBACK_LIFT).normalize() * self.force
+    }
+}
+
+//Movement input is ignored until the timer runs out, so a knockback isn't steered straight back out of.
+//Lives on anything that can be knocked back and is restarted in place, so it already counts on the frame of the hit
+#[derive(Component, Clone)]
+pub struct HitStun(pub Timer);
+impl Default for HitStun {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(0., false);
+        timer.tick(Duration::ZERO);
+        HitStun(timer)
+    }
+}
+impl HitStun {
+    pub fn stun(&mut self, time: f32) {
+        self.0 = Timer::from_seconds(time, false);
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        !self.0.finished()
+    }
+}
+
+//================================================================
+
+pub const STATUS_DAMAGE_TICK: f32 = 0.5;
+
+pub const BURN_TINT: Color = Color::rgb(1., 0.55, 0.3);
+pub const POISON_TINT: Color = Color::rgb(0.55, 1., 0.4);
+pub const SLOW_TINT: Color = Color::rgb(0.6, 0.7, 1.);
+pub const FREEZE_TINT: Color = Color::rgb(0.5, 0.9, 1.);
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fire,
+    Poison,
+    Alchemical,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Damage {
+    pub amount: i32,
+    pub damage_type: DamageType,
+    pub status: Option<StatusEffect>,
+}
+
+//Damage type -> fraction of that damage ignored. 1 is immune, negative takes extra damage
+#[derive(Component, Clone, Default, Debug)]
+pub struct Resistances(pub HashMap<DamageType, f32>);
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        self.0.get(&damage_type).copied().unwrap_or(0.)
+    }
+    pub fn reduce(&self, damage_type: DamageType, amount: i32) -> i32 {
+        (amount as f32 * (1. - self.get(damage_type))).round().max(0.) as i32
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum StatusEffect {
+    //Damage every STATUS_DAMAGE_TICK
+    Burn { damage: i32, duration: f32 },
+    Poison { damage: i32, duration: f32 },
+    //Fraction of max speed taken away. Only horizontal speed, so falling isn't slowed
+    Slow { amount: f32, duration: f32 },
+    Freeze { duration: f32 },
+}
+impl StatusEffect {
+    pub fn duration(&self) -> f32 {
+        match *self {
+            StatusEffect::Burn { duration, .. }     => duration,
+            StatusEffect::Poison { duration, .. }   => duration,
+            StatusEffect::Slow { duration, .. }     => duration,
+            StatusEffect::Freeze { duration }       => duration,
+        }
+    }
+    pub fn tint(&self) -> Color {
+        match self {
+            StatusEffect::Burn { .. }   => BURN_TINT,
+            StatusEffect::Poison { .. } => POISON_TINT,
+            StatusEffect::Slow { .. }   => SLOW_TINT,
+            StatusEffect::Freeze { .. } => FREEZE_TINT,
+        }
+    }
+    pub fn damage_over_time(&self) -> Option<(i32, DamageType)> {
+        match *self {
+            StatusEffect::Burn { damage, .. }   => Some((damage, DamageType::Fire)),
+            StatusEffect::Poison { damage, .. } => Some((damage, DamageType::Poison)),
+            _ => None,
+        }
+    }
+    pub fn speed_multiplier(&self) -> f32 {
+        match *self {
+            StatusEffect::Slow { amount, .. }   => (1. - amount).clamp(0., 1.),
+            StatusEffect::Freeze { .. }         => 0.,
+            _ => 1.,
+        }
+    }
+    fn same_kind(&self, other: &StatusEffect) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+#[derive(Clone)]
+pub struct ActiveStatusEffect {
+    pub effect: StatusEffect,
+    pub timer: Timer,
+    pub damage_tick: Timer,
+}
+
+#[derive(Component, Clone, Default)]
+pub struct StatusEffects {
+    pub active: Vec<ActiveStatusEffect>,
+    //The max velocity from before anything slowed the entity, put back once nothing does
+    pub base_max_velocity: Option<MaxVelocity>,
+}
+impl StatusEffects {
+    //The same kind of effect again replaces the old one and starts it over
+    pub fn add(&mut self, effect: StatusEffect) {
+        self.active.retain(|active| !active.effect.same_kind(&effect));
+        self.active.push(ActiveStatusEffect {
+            effect,
+            timer: Timer::from_seconds(effect.duration(), false),
+            damage_tick: Timer::from_seconds(STATUS_DAMAGE_TICK, true),
+        });
+    }
 }
 
 #[derive(Component, Default, Clone)]
@@ -123,4 +327,22 @@ impl HealthFlash {
 
 pub struct EntityDiedEvent(pub Entity);
 
+//================================================================
+
+//Ldtk entity instances don't have iids in this version, so build a stable
+//one out of the level, definition and spawn position of the entity
+#[derive(Component, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntityIid {
+    pub level_uid: i32,
+    pub iid: String,
+}
+impl EntityIid {
+    pub fn from_entity_info(entity_instance: &EntityInstance, layer_instance: &LayerInstance) -> Self {
+        EntityIid {
+            level_uid: layer_instance.level_id,
+            iid: format!("{}_{}_{}", entity_instance.def_uid, entity_instance.px.x, entity_instance.px.y),
+        }
+    }
+}
+
 //================================================================
\ No newline at end of file
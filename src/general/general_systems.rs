@@ -2,9 +2,9 @@ use bevy::prelude::*;
 use heron::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
-use crate::player::player_components::Player;
+use crate::{player::player_components::Player, physics::physics_components::MaxVelocity};
 
-use super::{tools, general_components::*};
+use super::{tools, general_components::*, GameState};
 
 //================================================================
 
@@ -15,28 +15,47 @@ pub fn setup(
     commands.spawn_bundle(OrthographicCameraBundle::new_2d())
         .insert(GameCamera);
 
-    //let ldtk_handle = assets.load("Tilemaps/TileMapMain.ldtk");
-    let ldtk_handle = assets.load("Tilemaps/TileMapTheNew.ldtk");
-    commands.spawn_bundle(LdtkWorldBundle {
-        ldtk_handle,
-        ..Default::default()
-    });
+    //Lets definition files (enemies, etc) be edited while the game is running
+    if let Err(e) = assets.watch_for_changes() {
+        println!("Unable to watch assets for changes: {:?}", e);
+    }
+}
 
+//Runs straight after Time is updated, before anything reads it
+pub fn update_frame_delta(
+    time: Res<Time>,
+    mut frame_delta: ResMut<FrameDelta>,
+) {
+    frame_delta.update(&time);
 }
 
 pub fn pause_physics_while_load(
     mut level_events: EventReader<LevelEvent>,
     mut physics_time: ResMut<PhysicsTime>,
+    game_state: Res<State<GameState>>,
 ) {
     for event in level_events.iter() {
         match event {
             LevelEvent::SpawnTriggered(_) => physics_time.set_scale(0.),
-            LevelEvent::Transformed(_) => physics_time.set_scale(1.),
+            //Levels can finish loading behind a menu, which should keep physics paused
+            LevelEvent::Transformed(_) if *game_state.current() == GameState::Playing => physics_time.set_scale(1.),
             _ => (),
         }
     }
 }
 
+pub fn pause_physics(
+    mut physics_time: ResMut<PhysicsTime>,
+) {
+    physics_time.set_scale(0.);
+}
+
+pub fn resume_physics(
+    mut physics_time: ResMut<PhysicsTime>,
+) {
+    physics_time.set_scale(1.);
+}
+
 //================================================================
 
 pub fn fade_in_out(
@@ -72,29 +91,69 @@ pub fn fade_in_out(
 //================================================================
 
 pub fn change_health(
-    mut health_query: Query<(Entity, &mut Health)>,
+    mut health_query: Query<(
+        Entity, &mut Health, Option<&GlobalTransform>, Option<&mut Velocity>,
+        Option<&Resistances>, Option<&mut StatusEffects>, Option<&mut HitStun>,
+    )>,
     mut health_event: EventReader<HealthChangeEvent>,
     mut died_event: EventWriter<EntityDiedEvent>,
     mut commands: Commands,
 ) {
     for event in health_event.iter() {
-        if let Ok((entity, mut health)) = health_query.get_mut(event.entity) {
+        if let Ok((entity, mut health, transform, velocity, resistances, status_effects, hit_stun)) = health_query.get_mut(event.entity) {
 
-            match event.change_type {
+            let added = match &event.change_type {
                 HealthChangeType::Set { value } => {
-                    health.set_health(value);
+                    health.set_health(*value);
+                    None
                 },
-                HealthChangeType::Add { value } => {
-
-                    if value > 0 {  //Healing
-                        commands.entity(entity).insert(HealthFlash::new(Color::WHITE, Color::GREEN, 0.2));
-                    }
-                    else if value < 0 && !health.invincible() { //Damage
-                        commands.entity(entity).insert(HealthFlash::new(Color::WHITE, Color::RED, 0.2));
+                HealthChangeType::Add { value } => Some(*value),
+                HealthChangeType::Drain { value } => {
+                    health.drain(*value);
+                    None
+                },
+                HealthChangeType::Damage(damage) => {
+                    let resistance = resistances.map_or(0., |resistances| resistances.get(damage.damage_type));
+
+                    //Status effects only stick when the hit lands and could hurt
+                    if let Some(effect) = damage.status {
+                        if resistance < 1. && !health.invincible() {
+                            match status_effects {
+                                Some(mut status_effects) => status_effects.add(effect),
+                                None => {
+                                    let mut status_effects = StatusEffects::default();
+                                    status_effects.add(effect);
+                                    commands.entity(entity).insert(status_effects);
+                                },
+                            }
+                        }
                     }
 
-                    health.add_health(value);
+                    Some(-resistances.map_or(damage.amount, |resistances| resistances.reduce(damage.damage_type, damage.amount)))
                 },
+            };
+
+            if let Some(value) = added {
+
+                if value > 0 {  //Healing
+                    commands.entity(entity).insert(HealthFlash::new(Color::WHITE, Color::GREEN, 0.2));
+                }
+                else if value < 0 && !health.invincible() { //Damage
+                    commands.entity(entity).insert(HealthFlash::new(Color::WHITE, Color::RED, 0.2));
+
+                    if let (Some(knockback), Some(transform)) = (event.knockback, transform) {
+                        if let Some(mut velocity) = velocity {
+                            velocity.linear = knockback.impulse(transform.translation.truncate()).extend(0.);
+                        }
+
+                        //Never stunned for longer than the iframes, so nothing can be stun locked
+                        if let Some(mut hit_stun) = hit_stun {
+                            hit_stun.stun(HIT_STUN_TIME.min(health.iframe_time()));
+                        }
+                    }
+                }
+
+                health.add_health(value);
             }
             if health.get_health() <= 0 {
                 died_event.send(EntityDiedEvent(entity));
@@ -103,9 +162,90 @@ pub fn change_health(
     }
 }
 
+pub fn update_status_effects(
+    mut status_query: Query<(
+        Entity, &mut StatusEffects, &Health, Option<&Resistances>,
+        Option<&mut MaxVelocity>, Option<&mut Velocity>,
+        Option<&mut Sprite>, Option<&mut TextureAtlasSprite>, Option<&HealthFlash>,
+    )>,
+    mut health_event: EventWriter<HealthChangeEvent>,
+    time: Res<FrameDelta>,
+    mut commands: Commands,
+) {
+    for (entity, mut status_effects, health, resistances, max_velocity, velocity, sprite, atlas_sprite, flash) in status_query.iter_mut() {
+
+        for active in status_effects.active.iter_mut() {
+            active.timer.tick(time.delta());
+            active.damage_tick.tick(time.delta());
+
+            if let Some((damage, damage_type)) = active.effect.damage_over_time() {
+                //Goes through change_health, so deaths are only ever sent from one place
+                if active.damage_tick.just_finished() && health.get_health() > 0 {
+                    health_event.send(HealthChangeEvent {
+                        entity,
+                        change_type: HealthChangeType::Drain {
+                            value: resistances.map_or(damage, |resistances| resistances.reduce(damage_type, damage)),
+                        },
+                        knockback: None,
+                    });
+                }
+            }
+        }
+        status_effects.active.retain(|active| !active.timer.finished());
+
+        let speed_multiplier = status_effects.active.iter()
+            .map(|active| active.effect.speed_multiplier())
+            .fold(1., f32::min);
+
+        if let Some(mut max_velocity) = max_velocity {
+            if speed_multiplier < 1. {
+                let base_x = status_effects.base_max_velocity.get_or_insert_with(|| max_velocity.clone()).x;
+                max_velocity.x = base_x * speed_multiplier;
+            }
+            else if let Some(base_max_velocity) = status_effects.base_max_velocity.take() {
+                *max_velocity = base_max_velocity;
+            }
+        }
+
+        //Frozen entities stay right where they are
+        if speed_multiplier == 0. {
+            if let Some(mut velocity) = velocity {
+                velocity.linear = Vec3::ZERO;
+            }
+        }
+
+        //Health flashes take over the colour until they finish. Alpha is left for fading
+        if flash.is_none() {
+            let tint = status_effects.active.last().map_or(Color::WHITE, |active| active.effect.tint());
+
+            if let Some(mut sprite) = sprite {
+                let alpha = sprite.color.a();
+                sprite.color = *tint.clone().set_a(alpha);
+            }
+            if let Some(mut atlas_sprite) = atlas_sprite {
+                let alpha = atlas_sprite.color.a();
+                atlas_sprite.color = *tint.clone().set_a(alpha);
+            }
+        }
+
+        if status_effects.active.is_empty() && status_effects.base_max_velocity.is_none() {
+            commands.entity(entity).remove::<StatusEffects>();
+        }
+    }
+}
+
+pub fn tick_hit_stun(
+    mut stun_query: Query<&mut HitStun>,
+    time: Res<FrameDelta>,
+) {
+    for mut stun in stun_query.iter_mut() {
+        stun.0.tick(time.delta());
+    }
+}
+
 pub fn do_iframes(
     mut health_query: Query<&mut Health>,
-    time: Res<Time>,
+    time: Res<FrameDelta>,
 ) {
     for mut health in health_query.iter_mut() {
         health.tick(time.delta());
@@ -164,11 +304,13 @@ pub fn resolve_entity_death(
 
     for event in died_event.iter() {
 
-        if let Ok(_) = player_query.get(event.0) {
-            println!("Player died");
+        //The player is respawned at the last checkpoint instead
+        if player_query.get(event.0).is_ok() {
+            continue;
         }
 
-        commands.entity(event.0).despawn();
+        //Recursive so anything held, like an enemy's weapons, goes with it
+        commands.entity(event.0).despawn_recursive();
     }
 }
 
@@ -1,3 +1,61 @@
+//===============================================================
+
+use bevy::{prelude::*, core::CoreSystem};
+
 pub mod tools;
 pub mod general_systems;
-pub mod general_components;
\ No newline at end of file
+pub mod general_components;
+
+//===============================================================
+
+//Top level state of the game. Gameplay systems only run while Playing,
+//and physics is paused in every other state
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum GameState {
+    MainMenu,
+    Playing,
+    Paused,
+    Map,
+    Dead,
+    Ending,
+}
+
+//===============================================================
+
+//Health and other systems shared by every entity type
+pub struct GeneralPlugin;
+impl Plugin for GeneralPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<general_components::FrameDelta>()
+            .add_system_to_stage(CoreStage::First, general_systems::update_frame_delta.after(CoreSystem::Time))
+            .add_system(general_systems::fade_in_out)
+
+            .add_event::<general_components::HealthChangeEvent>()
+            .add_event::<general_components::EntityDiedEvent>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    //Damage lands before anything moves, so a knockback and its stun take effect on the frame of the hit
+                    .with_system(general_systems::tick_hit_stun.label("ApplyDamage").before("ChangeHealth"))
+                    .with_system(general_systems::change_health.label("ApplyDamage").label("ChangeHealth"))
+                    .with_system(general_systems::health_flash)
+                    .with_system(general_systems::do_iframes)
+                    .with_system(general_systems::update_status_effects.label("ApplyDamage").before("ChangeHealth").after("PlayerSprint"))
+                    //Only change_health sends EntityDiedEvent. Anything reading it runs between the two,
+                    //so it sees the event on the frame of the death and the entity before it's despawned
+                    .with_system(general_systems::resolve_entity_death.label("ResolveDeath").after("ChangeHealth"))
+            )
+
+            .add_system_set(
+                SystemSet::on_exit(GameState::Playing)
+                    .with_system(general_systems::pause_physics)
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Playing)
+                    .with_system(general_systems::resume_physics)
+            )
+        ;
+    }
+}
+
+//===============================================================
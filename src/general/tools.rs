@@ -1,4 +1,6 @@
 use bevy::{math::Vec2, sprite::TextureAtlas, prelude::{Assets, Handle, AssetServer}};
+use bevy_ecs_ldtk::{EntityInstance, ldtk::{FieldInstance, Level}, prelude::FieldValue};
+use serde::{Serialize, de::DeserializeOwned};
 
 
 
@@ -27,11 +29,11 @@ pub fn clamp_shift(mut val: f32, min: f32, max: f32, by: f32) -> f32 {
 
     if val < min {
         val += by;
-        return val.min(min);
+        val.min(min)
     }
     else {
         val -= by;
-        return val.max(max);
+        val.max(max)
     }
 }
 
@@ -47,5 +49,72 @@ pub fn load_texture_atlas(
 
     let texture_handle = assets.load(asset_path);
     let texture_atlas = TextureAtlas::from_grid(texture_handle, tile_size, columns, rows);
-    return texture_atlases.add(texture_atlas);
-}
\ No newline at end of file
+    texture_atlases.add(texture_atlas)
+}
+
+//Reads a String or Enum field from an LDtk entity
+pub fn get_string_field(entity_instance: &EntityInstance, identifier: &str) -> Option<String> {
+    find_string_field(&entity_instance.field_instances, identifier)
+}
+
+//Reads a String or Enum field from an LDtk level
+pub fn get_level_string_field(level: &Level, identifier: &str) -> Option<String> {
+    find_string_field(&level.field_instances, identifier)
+}
+
+fn find_string_field(field_instances: &[FieldInstance], identifier: &str) -> Option<String> {
+
+    for instance in field_instances.iter() {
+        if instance.identifier == identifier {
+            match instance.value.clone() {
+                FieldValue::String(Some(value)) |
+                FieldValue::Enum(Some(value)) => {
+                    return Some(value);
+                },
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+//Reads a RON file. Missing files are None without an error, since most are only written once something changes
+pub fn load_ron<T: DeserializeOwned>(path: &str) -> Option<T> {
+
+    let contents = std::fs::read_to_string(path).ok()?;
+
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            println!("Failed to read {}: {}", path, e);
+            None
+        },
+    }
+}
+
+//Like load_ron, but writes out the defaults if there is no file so there is one to edit
+pub fn load_ron_or_default<T: DeserializeOwned + Serialize + Default>(path: &str) -> T {
+    match load_ron(path) {
+        Some(value) => value,
+        None => {
+            let value = T::default();
+            save_ron(&value, path);
+            value
+        },
+    }
+}
+
+pub fn save_ron<T: Serialize>(value: &T, path: &str) {
+
+    let contents = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(e) => {
+            println!("Failed to serialize {}: {}", path, e);
+            return
+        },
+    };
+
+    if let Err(e) = std::fs::write(path, contents) {
+        println!("Failed to write {}: {}", path, e);
+    }
+}
@@ -0,0 +1,40 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+//===============================================================
+
+//Something the player can use with the interact action when close enough
+#[derive(Component, Clone, Debug)]
+pub struct Interactable {
+    pub prompt: String,
+    pub range: f32,
+    pub enabled: bool,
+}
+impl Default for Interactable {
+    fn default() -> Self {
+        Interactable {
+            prompt: "Interact".to_string(),
+            range: 40.,
+            enabled: true,
+        }
+    }
+}
+impl Interactable {
+    pub fn new(prompt: &str, range: f32) -> Self {
+        Interactable {
+            prompt: prompt.to_string(),
+            range,
+            enabled: true,
+        }
+    }
+}
+
+//The closest interactable in range of the player this frame
+#[derive(Default)]
+pub struct InteractionTarget(pub Option<Entity>);
+
+//Sent when the player interacts with an entity. The interact press is consumed when this is sent
+pub struct InteractEvent(pub Entity);
+
+//===============================================================
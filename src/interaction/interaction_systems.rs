@@ -0,0 +1,57 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+use super::interaction_components::*;
+
+use crate::{
+    player::player_components::Player,
+    controls::controls_components::{ActionInput, ActionMap, PlayerAction},
+};
+
+//===============================================================
+
+pub fn find_interaction_target(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    interactable_query: Query<(Entity, &GlobalTransform, &Interactable), Without<Player>>,
+    mut target: ResMut<InteractionTarget>,
+) {
+    let mut closest: Option<(Entity, f32)> = None;
+
+    for player_pos in player_query.iter() {
+        for (entity, interactable_pos, interactable) in interactable_query.iter() {
+            if !interactable.enabled {
+                continue;
+            }
+
+            let distance = player_pos.translation.truncate().distance(interactable_pos.translation.truncate());
+            if distance > interactable.range {
+                continue;
+            }
+
+            match closest {
+                Some((_, closest_distance)) if closest_distance <= distance => {},
+                _ => closest = Some((entity, distance)),
+            }
+        }
+    }
+
+    target.0 = closest.map(|(entity, _)| entity);
+}
+
+pub fn interact_with_target(
+    target: Res<InteractionTarget>,
+    mut action_input: ResMut<ActionInput>,
+    action_map: Res<ActionMap>,
+    mut interact_event: EventWriter<InteractEvent>,
+) {
+    if let Some(entity) = target.0 {
+        if action_input.just_pressed(PlayerAction::Interact) {
+            interact_event.send(InteractEvent(entity));
+            //Stops sprint or anything else sharing the button from also firing
+            action_input.consume(PlayerAction::Interact, &action_map);
+        }
+    }
+}
+
+//===============================================================
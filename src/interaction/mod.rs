@@ -0,0 +1,29 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+pub mod interaction_components;
+mod interaction_systems;
+
+//===============================================================
+
+pub struct InteractionPlugin;
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(interaction_components::InteractionTarget::default())
+            .add_event::<interaction_components::InteractEvent>()
+
+            //Runs before any player systems so a consumed press is never seen by them
+            .add_system_to_stage(CoreStage::PreUpdate, interaction_systems::find_interaction_target
+                .label("FindInteractionTarget")
+                .after("UpdateActionInput")
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, interaction_systems::interact_with_target
+                .after("FindInteractionTarget")
+            )
+        ;
+    }
+}
+
+//===============================================================
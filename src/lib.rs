@@ -0,0 +1,34 @@
+//===============================================================
+
+//Systems take their queries and resources as arguments, so these are how bevy code looks
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+//bevy 0.6's Bundle derive forgets each component after moving it out
+#![allow(clippy::forget_non_drop)]
+
+pub mod world;
+pub mod physics;
+pub mod animation;
+pub mod player;
+pub mod non_player;
+pub mod ui;
+pub mod save;
+pub mod controls;
+pub mod interaction;
+pub mod replay;
+pub mod progression;
+pub mod transition;
+pub mod map;
+pub mod menu;
+pub mod settings;
+pub mod audio;
+pub mod navigation;
+pub mod boss;
+
+pub mod general;
+
+
+pub mod weapons;
+
+pub mod simulation;
+
+//===============================================================
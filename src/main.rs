@@ -1,24 +1,21 @@
 //===============================================================
 
+//Systems take their queries and resources as arguments, so these are how bevy code looks
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+//bevy 0.6's Bundle derive forgets each component after moving it out
+#![allow(clippy::forget_non_drop)]
+
 use bevy::{prelude::*, render::render_resource::TextureUsages};
 use bevy_ecs_ldtk::prelude::*;
 use heron::prelude::*;
 use bevy_prototype_lyon::prelude::*;
-use bevy_egui::{egui, EguiContext, EguiPlugin};
+use bevy_egui::EguiPlugin;
 
 //===============================================================
 
-mod world;
-mod physics;
-mod animation;
-mod player;
-mod non_player;
-mod ui;
-
-mod general;
-
-
-mod weapons;
+use metroidvania_game_jam_v2::{
+    world, physics, animation, player, non_player, ui, save, controls, interaction, replay, progression, transition, map, menu, settings, audio, navigation, boss, general, weapons,
+};
 
 //===============================================================
 
@@ -37,21 +34,34 @@ fn main() {
         .add_plugin(EguiPlugin)
 
         //Add Own plugins
+        .add_plugin(settings::SettingsPlugin)
+        .add_plugin(controls::ControlsPlugin)
+        .add_plugin(interaction::InteractionPlugin)
+        .add_plugin(general::GeneralPlugin)
         .add_plugin(animation::AnimationPlugin)
         .add_plugin(physics::CustomPhysicsPlugin)
         .add_plugin(player::PlayerPlugin)
         .add_plugin(weapons::WeaponPlugin)
         .add_plugin(world::WorldPlugin)
         .add_plugin(non_player::NonPlayerPlugin)
+        .add_plugin(navigation::NavigationPlugin)
+        .add_plugin(boss::BossPlugin)
         .add_plugin(ui::UiPlugin)
+        .add_plugin(progression::ProgressionPlugin)
+        .add_plugin(transition::TransitionPlugin)
+        .add_plugin(map::MapPlugin)
+        .add_plugin(menu::MenuPlugin)
+        .add_plugin(audio::GameAudioPlugin)
+        .add_plugin(save::SavePlugin)
+        .add_plugin(replay::ReplayPlugin)
 
         //-------------------------------------------------
 
-        .insert_resource(Msaa { samples: 4 })
-        .insert_resource(Gravity::from(Vec2::new(0., -500.,)))
+        .insert_resource(Gravity::from(Vec2::new(0., physics::physics_components::GRAVITY)))
         
         //===============================================================
 
+        .add_state(general::GameState::MainMenu)
         .insert_resource(LevelSelection::Uid(0))
         .insert_resource(LdtkSettings {
             load_level_neighbors: true,
@@ -68,17 +78,6 @@ fn main() {
 
         .add_system(general::general_systems::pause_physics_while_load)
 
-        .add_system(general::general_systems::fade_in_out)
-
-        //-------------------------------------------------
-
-        .add_event::<general::general_components::HealthChangeEvent>()
-        .add_event::<general::general_components::EntityDiedEvent>()
-        .add_system(general::general_systems::change_health)
-        .add_system(general::general_systems::health_flash)
-        .add_system(general::general_systems::do_iframes)
-        .add_system(general::general_systems::resolve_entity_death)
-
         //-------------------------------------------------
 
         .run();
@@ -92,15 +91,12 @@ pub fn set_texture_filters_to_nearest(
 ) {
     // quick and dirty, run this for all textures anytime a texture is created.
     for event in texture_events.iter() {
-        match event {
-            AssetEvent::Created { handle } => {
-                if let Some(mut texture) = textures.get_mut(handle) {
-                    texture.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
-                        | TextureUsages::COPY_SRC
-                        | TextureUsages::COPY_DST;
-                }
+        if let AssetEvent::Created { handle } = event {
+            if let Some(texture) = textures.get_mut(handle) {
+                texture.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_SRC
+                    | TextureUsages::COPY_DST;
             }
-            _ => (),
         }
     }
 }
@@ -0,0 +1,94 @@
+//===============================================================
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::GridCoords;
+use bevy_egui::egui::{self, Color32};
+
+//===============================================================
+
+pub const MAP_WIDTH: f32 = 640.;
+pub const MAP_HEIGHT: f32 = 400.;
+
+pub const MAP_DEFAULT_ZOOM: f32 = 0.1;
+pub const MAP_MIN_ZOOM: f32 = 0.02;
+pub const MAP_MAX_ZOOM: f32 = 1.;
+//How much one unit of mouse scroll changes the zoom
+pub const MAP_ZOOM_SPEED: f32 = 0.002;
+
+pub const MAP_BACKGROUND_COLOR: Color32 = Color32::from_black_alpha(220);
+pub const MAP_LEVEL_COLOR: Color32 = Color32::from_gray(60);
+pub const MAP_CURRENT_LEVEL_COLOR: Color32 = Color32::from_gray(100);
+pub const MAP_LEVEL_OUTLINE_COLOR: Color32 = Color32::from_gray(180);
+pub const MAP_PLAYER_COLOR: Color32 = Color32::from_rgb(255, 60, 60);
+pub const MAP_ITEM_COLOR: Color32 = Color32::GOLD;
+pub const MAP_SAVE_COLOR: Color32 = Color32::from_rgb(0, 255, 255);
+pub const MAP_ACTIVE_SAVE_COLOR: Color32 = Color32::from_rgb(0, 255, 0);
+
+pub const MINIMAP_WIDTH: f32 = 160.;
+pub const MINIMAP_HEIGHT: f32 = 120.;
+//Size of one level tile on the minimap
+pub const MINIMAP_TILE_SIZE: f32 = 3.;
+
+//How many tiles away the player can see, and how many directions are checked
+pub const MINIMAP_REVEAL_RADIUS: f32 = 10.;
+pub const MINIMAP_REVEAL_RAYS: usize = 64;
+
+pub const MINIMAP_FLOOR_COLOR: Color32 = Color32::from_gray(40);
+pub const MINIMAP_WALL_COLOR: Color32 = Color32::from_gray(170);
+pub const MINIMAP_ENEMY_COLOR: Color32 = Color32::from_rgb(255, 140, 0);
+
+//===============================================================
+
+//Uids of every level the player has been in. Only these are drawn on the map
+#[derive(Default, Clone, Debug)]
+pub struct VisitedLevels(pub HashSet<i32>);
+
+//Level uid -> tiles the player has seen in that level
+#[derive(Default, Clone, Debug)]
+pub struct RevealedTiles(pub HashMap<i32, HashSet<GridCoords>>);
+impl RevealedTiles {
+    pub fn is_revealed(&self, level_uid: i32, coords: GridCoords) -> bool {
+        match self.0.get(&level_uid) {
+            Some(revealed) => revealed.contains(&coords),
+            None => false,
+        }
+    }
+}
+
+//What part of the world the map is showing. Center is in LDtk world pixels, which go down in y
+#[derive(Clone, Debug)]
+pub struct MapView {
+    pub center: Vec2,
+    pub zoom: f32,
+}
+impl Default for MapView {
+    fn default() -> Self {
+        MapView {
+            center: Vec2::ZERO,
+            zoom: MAP_DEFAULT_ZOOM,
+        }
+    }
+}
+impl MapView {
+    pub fn to_screen(&self, position: Vec2, map_rect: egui::Rect) -> egui::Pos2 {
+        let offset = (position - self.center) * self.zoom;
+        map_rect.center() + egui::Vec2::new(offset.x, offset.y)
+    }
+
+    pub fn pan(&mut self, screen_delta: egui::Vec2) {
+        self.center -= Vec2::new(screen_delta.x, screen_delta.y) / self.zoom;
+    }
+
+    pub fn zoom_by(&mut self, scroll: f32) {
+        self.zoom = (self.zoom * (1. + scroll * MAP_ZOOM_SPEED)).clamp(MAP_MIN_ZOOM, MAP_MAX_ZOOM);
+    }
+}
+
+//Bevy translations go up in y, the LDtk world goes down
+pub fn translation_to_ldtk(translation: Vec2, world_height: i32) -> Vec2 {
+    Vec2::new(translation.x, world_height as f32 - translation.y)
+}
+
+//===============================================================
@@ -0,0 +1,325 @@
+//===============================================================
+
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_egui::{EguiContext, egui::{self, Align2, Sense, Stroke}};
+
+use super::map_components::*;
+
+use crate::{
+    player::player_components::Player,
+    non_player::non_player_components::NonPlayer,
+    general::{GameState, general_components::EntityIid},
+    save::save_components::SaveData,
+    world::{LevelChangedEvent, ActiveCheckpoint, LevelWallGrids, PickupCollected},
+};
+
+//===============================================================
+
+pub fn track_visited_levels(
+    mut level_changed_event: EventReader<LevelChangedEvent>,
+    current_level: Res<LevelSelection>,
+    mut visited: ResMut<VisitedLevels>,
+) {
+    for event in level_changed_event.iter() {
+        visited.0.insert(event.0);
+    }
+
+    //The starting level is selected without an event
+    if current_level.is_changed() {
+        if let LevelSelection::Uid(uid) = *current_level {
+            visited.0.insert(uid);
+        }
+    }
+}
+
+//===============================================================
+
+pub fn center_map_on_player(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    world_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    mut map_view: ResMut<MapView>,
+) {
+    let ldtk_asset = match world_query.get_single().ok().and_then(|handle| ldtk_assets.get(handle)) {
+        Some(ldtk_asset) => ldtk_asset,
+        None => return,
+    };
+
+    if let Ok(transform) = player_query.get_single() {
+        map_view.center = translation_to_ldtk(transform.translation.truncate(), ldtk_asset.world_height());
+    }
+}
+
+pub fn show_map(
+    mut egui_context: ResMut<EguiContext>,
+    mut game_state: ResMut<State<GameState>>,
+    mut map_view: ResMut<MapView>,
+    visited: Res<VisitedLevels>,
+    current_level: Res<LevelSelection>,
+    save_data: Res<SaveData>,
+    checkpoint: Res<Option<ActiveCheckpoint>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    world_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+) {
+    let ldtk_asset = match world_query.get_single().ok().and_then(|handle| ldtk_assets.get(handle)) {
+        Some(ldtk_asset) => ldtk_asset,
+        None => return,
+    };
+    let world_height = ldtk_asset.world_height();
+
+    let player_position = player_query
+        .get_single()
+        .ok()
+        .map(|transform| translation_to_ldtk(transform.translation.truncate(), world_height));
+    let active_checkpoint = (*checkpoint)
+        .as_ref()
+        .map(|checkpoint| translation_to_ldtk(checkpoint.position, world_height));
+
+    let mut back = false;
+
+    egui::Window::new("Map")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_context.ctx_mut(), |ui| {
+
+        let (response, painter) = ui.allocate_painter(egui::Vec2::new(MAP_WIDTH, MAP_HEIGHT), Sense::drag());
+
+        map_view.pan(response.drag_delta());
+        if response.hovered() {
+            map_view.zoom_by(ui.input().scroll_delta.y);
+        }
+
+        let map_rect = response.rect;
+        painter.rect_filled(map_rect, 0., MAP_BACKGROUND_COLOR);
+
+        //Levels first so the icons are drawn on top of them
+        let visited_levels: Vec<_> = ldtk_asset.project.levels
+            .iter()
+            .filter(|level| visited.0.contains(&level.uid))
+            .collect();
+
+        for level in visited_levels.iter() {
+            let level_rect = egui::Rect::from_two_pos(
+                map_view.to_screen(Vec2::new(level.world_x as f32, level.world_y as f32), map_rect),
+                map_view.to_screen(Vec2::new((level.world_x + level.px_wid) as f32, (level.world_y + level.px_hei) as f32), map_rect),
+            );
+
+            let color = if *current_level == LevelSelection::Uid(level.uid) {
+                MAP_CURRENT_LEVEL_COLOR
+            }
+            else {
+                MAP_LEVEL_COLOR
+            };
+            painter.rect_filled(level_rect, 0., color);
+            painter.rect_stroke(level_rect, 0., Stroke::new(1., MAP_LEVEL_OUTLINE_COLOR));
+        }
+
+        for level in visited_levels.iter() {
+            let level_origin = Vec2::new(level.world_x as f32, level.world_y as f32);
+
+            for layer in level.layer_instances.iter().flatten() {
+                for entity_instance in layer.entity_instances.iter() {
+
+                    //Center of the entity, the same point its translation ends up at
+                    let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+                    let position = level_origin + entity_instance.px.as_vec2() + (Vec2::splat(0.5) - entity_instance.pivot) * size;
+                    let screen_position = map_view.to_screen(position, map_rect);
+
+                    match entity_instance.identifier.as_str() {
+                        "ItemPickup" => {
+                            let iid = EntityIid::from_entity_info(entity_instance, layer);
+                            if !save_data.is_pickup_collected(iid.level_uid, &iid.iid) {
+                                painter.circle_filled(screen_position, 3., MAP_ITEM_COLOR);
+                            }
+                        },
+                        "Checkpoint" => {
+                            let is_active = active_checkpoint.is_some_and(|active| active.distance(position) < 1.);
+                            let color = if is_active { MAP_ACTIVE_SAVE_COLOR } else { MAP_SAVE_COLOR };
+                            painter.rect_filled(egui::Rect::from_center_size(screen_position, egui::Vec2::new(4., 6.)), 0., color);
+                        },
+                        _ => {},
+                    }
+                }
+            }
+        }
+
+        if let Some(player_position) = player_position {
+            painter.circle_filled(map_view.to_screen(player_position, map_rect), 4., MAP_PLAYER_COLOR);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Back").clicked() {
+                back = true;
+            }
+            ui.label("Drag to pan, scroll to zoom");
+        });
+    });
+
+    if back {
+        let _ = game_state.set(GameState::Paused);
+    }
+}
+
+//===============================================================
+
+//Uid and bottom left corner of the loaded level the player is in
+fn current_level_origin(
+    level_query: &Query<(&Handle<LdtkLevel>, &Transform), Without<Player>>,
+    ldtk_levels: &Assets<LdtkLevel>,
+    current_level: &LevelSelection,
+) -> Option<(i32, Vec2)> {
+    level_query.iter().find_map(|(level_handle, level_transform)| {
+        let ldtk_level = ldtk_levels.get(level_handle)?;
+        if current_level.is_match(&0, &ldtk_level.level) {
+            Some((ldtk_level.level.uid, level_transform.translation.truncate()))
+        }
+        else {
+            None
+        }
+    })
+}
+
+fn to_grid_coords(tile_position: Vec2) -> GridCoords {
+    GridCoords {
+        x: tile_position.x.floor() as i32,
+        y: tile_position.y.floor() as i32,
+    }
+}
+
+//Casts rays out from the player and reveals every tile up to and including the first wall they hit
+pub fn reveal_minimap_tiles(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    level_query: Query<(&Handle<LdtkLevel>, &Transform), Without<Player>>,
+    ldtk_levels: Res<Assets<LdtkLevel>>,
+    current_level: Res<LevelSelection>,
+    wall_grids: Res<LevelWallGrids>,
+    mut revealed_tiles: ResMut<RevealedTiles>,
+) {
+    let (level_uid, level_origin) = match current_level_origin(&level_query, &ldtk_levels, &current_level) {
+        Some(level) => level,
+        None => return,
+    };
+    let wall_grid = match wall_grids.0.get(&level_uid) {
+        Some(wall_grid) => wall_grid,
+        None => return,
+    };
+    let player_pos = match player_query.get_single() {
+        Ok(transform) => transform.translation.truncate(),
+        Err(_) => return,
+    };
+
+    let start = (player_pos - level_origin) / wall_grid.grid_size as f32;
+    let revealed = revealed_tiles.0
+        .entry(level_uid)
+        .or_default();
+
+    for ray in 0..MINIMAP_REVEAL_RAYS {
+        let angle = ray as f32 / MINIMAP_REVEAL_RAYS as f32 * std::f32::consts::TAU;
+        let dir = Vec2::new(angle.cos(), angle.sin());
+
+        let mut distance = 0.;
+        while distance <= MINIMAP_REVEAL_RADIUS {
+            let coords = to_grid_coords(start + dir * distance);
+            if !wall_grid.in_bounds(coords) {
+                break;
+            }
+
+            revealed.insert(coords);
+            if wall_grid.is_wall(coords) {
+                break;
+            }
+
+            //Half a tile at a time so rays don't skip over thin walls
+            distance += 0.5;
+        }
+    }
+}
+
+pub fn show_minimap(
+    mut egui_context: ResMut<EguiContext>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    enemy_query: Query<&GlobalTransform, With<NonPlayer>>,
+    pickup_query: Query<(&GlobalTransform, &PickupCollected)>,
+    level_query: Query<(&Handle<LdtkLevel>, &Transform), Without<Player>>,
+    ldtk_levels: Res<Assets<LdtkLevel>>,
+    current_level: Res<LevelSelection>,
+    wall_grids: Res<LevelWallGrids>,
+    revealed_tiles: Res<RevealedTiles>,
+) {
+    let (level_uid, level_origin) = match current_level_origin(&level_query, &ldtk_levels, &current_level) {
+        Some(level) => level,
+        None => return,
+    };
+    let wall_grid = match wall_grids.0.get(&level_uid) {
+        Some(wall_grid) => wall_grid,
+        None => return,
+    };
+    let player_pos = match player_query.get_single() {
+        Ok(transform) => transform.translation.truncate(),
+        Err(_) => return,
+    };
+
+    //Positions on the minimap are measured in tiles from the bottom left of the level
+    let to_tile_position = |position: Vec2| (position - level_origin) / wall_grid.grid_size as f32;
+    let center = to_tile_position(player_pos);
+
+    egui::Window::new("Minimap")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(Align2::RIGHT_TOP, egui::Vec2::new(-10., 10.))
+        .show(egui_context.ctx_mut(), |ui| {
+
+        let (response, painter) = ui.allocate_painter(egui::Vec2::new(MINIMAP_WIDTH, MINIMAP_HEIGHT), Sense::hover());
+        let map_rect = response.rect;
+        painter.rect_filled(map_rect, 0., MAP_BACKGROUND_COLOR);
+
+        //Tiles go up in y, the screen goes down
+        let to_screen = |tile_position: Vec2| {
+            let offset = (tile_position - center) * MINIMAP_TILE_SIZE;
+            map_rect.center() + egui::Vec2::new(offset.x, -offset.y)
+        };
+
+        let half_width = (MINIMAP_WIDTH / MINIMAP_TILE_SIZE / 2.) as i32 + 1;
+        let half_height = (MINIMAP_HEIGHT / MINIMAP_TILE_SIZE / 2.) as i32 + 1;
+        let center_coords = to_grid_coords(center);
+
+        for y in center_coords.y - half_height..=center_coords.y + half_height {
+            for x in center_coords.x - half_width..=center_coords.x + half_width {
+
+                let coords = GridCoords { x, y };
+                if !revealed_tiles.is_revealed(level_uid, coords) {
+                    continue;
+                }
+
+                let color = if wall_grid.is_wall(coords) { MINIMAP_WALL_COLOR } else { MINIMAP_FLOOR_COLOR };
+                let tile_center = to_screen(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+                painter.rect_filled(egui::Rect::from_center_size(tile_center, egui::Vec2::splat(MINIMAP_TILE_SIZE)), 0., color);
+            }
+        }
+
+        //Things are only shown once the tile they are on has been seen
+        let draw_marker = |position: Vec2, radius: f32, color: egui::Color32| {
+            let tile_position = to_tile_position(position);
+            if revealed_tiles.is_revealed(level_uid, to_grid_coords(tile_position)) {
+                painter.circle_filled(to_screen(tile_position), radius, color);
+            }
+        };
+
+        for (transform, collected) in pickup_query.iter() {
+            if !collected.0 {
+                draw_marker(transform.translation.truncate(), 2., MAP_ITEM_COLOR);
+            }
+        }
+        for transform in enemy_query.iter() {
+            draw_marker(transform.translation.truncate(), 2., MINIMAP_ENEMY_COLOR);
+        }
+
+        painter.circle_filled(map_rect.center(), 2.5, MAP_PLAYER_COLOR);
+    });
+}
+
+//===============================================================
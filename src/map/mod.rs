@@ -0,0 +1,39 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+use crate::general::GameState;
+
+pub mod map_components;
+mod map_systems;
+
+//===============================================================
+
+pub struct MapPlugin;
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(map_components::VisitedLevels::default())
+            .insert_resource(map_components::MapView::default())
+            .insert_resource(map_components::RevealedTiles::default())
+
+            .add_system(map_systems::track_visited_levels)
+
+            .add_system_set(
+                SystemSet::on_enter(GameState::Map)
+                    .with_system(map_systems::center_map_on_player)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Map)
+                    .with_system(map_systems::show_map)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(map_systems::reveal_minimap_tiles)
+                    .with_system(map_systems::show_minimap)
+            )
+        ;
+    }
+}
+
+//===============================================================
@@ -0,0 +1,27 @@
+//===============================================================
+
+use serde::{Serialize, Deserialize};
+
+//===============================================================
+
+pub const CONFIG_FILE_PATH: &str = "config.ron";
+
+pub const DEFAULT_WORLD_PATH: &str = "Tilemaps/TileMapTheNew.ldtk";
+
+//===============================================================
+
+//Game wide options that aren't player settings. Loaded from and saved to CONFIG_FILE_PATH
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GameConfig {
+    //LDtk file to load the world from, relative to the assets folder
+    pub world_path: String,
+}
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            world_path: DEFAULT_WORLD_PATH.to_string(),
+        }
+    }
+}
+
+//===============================================================
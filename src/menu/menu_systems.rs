@@ -0,0 +1,102 @@
+//===============================================================
+
+use bevy::{prelude::*, app::AppExit};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_egui::{EguiContext, egui::{self, Align2}};
+
+use super::menu_components::*;
+
+use crate::{
+    general::{GameState, tools::load_ron_or_default},
+    save::save_components::SaveData,
+    world::ActiveCheckpoint,
+    ui::{CoinsCollected, Popups, ShowPopup},
+    settings::settings_components::SettingsOpen,
+    progression::progression_components::ProgressFlags,
+    map::map_components::{VisitedLevels, RevealedTiles},
+    non_player::non_player_components::DefeatedEnemies,
+};
+
+//===============================================================
+
+pub fn load_config(
+    mut config: ResMut<GameConfig>,
+) {
+    *config = load_ron_or_default(CONFIG_FILE_PATH);
+}
+
+pub fn spawn_world(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    let ldtk_handle = assets.load(config.world_path.as_str());
+    commands.spawn_bundle(LdtkWorldBundle {
+        ldtk_handle,
+        ..Default::default()
+    });
+}
+
+//===============================================================
+
+pub fn show_main_menu(
+    mut egui_context: ResMut<EguiContext>,
+    mut game_state: ResMut<State<GameState>>,
+    mut settings_open: ResMut<SettingsOpen>,
+    mut app_exit: EventWriter<AppExit>,
+
+    mut save_data: ResMut<SaveData>,
+    mut coins: ResMut<CoinsCollected>,
+    mut checkpoint: ResMut<Option<ActiveCheckpoint>>,
+    mut flags: ResMut<ProgressFlags>,
+    mut visited: ResMut<VisitedLevels>,
+    mut defeated: ResMut<DefeatedEnemies>,
+    mut revealed_tiles: ResMut<RevealedTiles>,
+    mut popup: ResMut<Popups>,
+    mut popup_state: ResMut<State<ShowPopup>>,
+) {
+    let mut new_game = false;
+    let mut continue_game = false;
+
+    egui::Window::new("Main Menu")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_context.ctx_mut(), |ui| {
+        ui.heading("Metroidvania");
+
+        if ui.button("New Game").clicked() {
+            new_game = true;
+        }
+        if ui.add_enabled(save_data.has_player(), egui::Button::new("Continue")).clicked() {
+            continue_game = true;
+        }
+        if ui.button("Settings").clicked() {
+            settings_open.0 = !settings_open.0;
+        }
+        if ui.button("Quit").clicked() {
+            app_exit.send(AppExit);
+        }
+    });
+
+    if new_game {
+        //Throw away the loaded save. The file is overwritten on the next autosave
+        *save_data = SaveData::default();
+        coins.0 = 0;
+        *checkpoint = None;
+        *flags = ProgressFlags::default();
+        *visited = VisitedLevels::default();
+        *defeated = DefeatedEnemies::default();
+        *revealed_tiles = RevealedTiles::default();
+
+        *popup = Popups::Intro;
+        let _ = popup_state.set(ShowPopup::Show);
+    }
+
+    if new_game || continue_game {
+        let _ = game_state.set(GameState::Playing);
+    }
+}
+
+//===============================================================
@@ -0,0 +1,32 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+use crate::general::GameState;
+
+pub mod menu_components;
+mod menu_systems;
+
+//===============================================================
+
+pub struct MenuPlugin;
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(menu_components::GameConfig::default())
+            .add_startup_system(menu_systems::load_config)
+
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu)
+                    .with_system(menu_systems::show_main_menu)
+            )
+            //The world is only loaded once the player picks a game to play
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu)
+                    .with_system(menu_systems::spawn_world)
+            )
+        ;
+    }
+}
+
+//===============================================================
@@ -0,0 +1,25 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+use crate::general::GameState;
+
+pub mod navigation_components;
+mod navigation_systems;
+
+//===============================================================
+
+pub struct NavigationPlugin;
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            //Goals are set by enemy behaviours each frame
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(navigation_systems::navigate_agents.label("MovementInput").after("EnemyBehaviour"))
+            )
+        ;
+    }
+}
+
+//===============================================================
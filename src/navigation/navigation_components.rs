@@ -0,0 +1,140 @@
+//===============================================================
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::GridCoords;
+
+use crate::{
+    world::{LevelWallGrid, LevelWallGrids},
+    physics::physics_components::{MoveDir, FullMoveDir},
+};
+
+//===============================================================
+
+pub const NAV_REPATH_TIME:          f32 = 0.5;
+pub const NAV_WAYPOINT_REACHED:     f32 = 4.;
+//Stops a search through a big level taking too long, the closest point found is used instead
+pub const NAV_MAX_SEARCH_NODES:     usize = 2000;
+
+//===============================================================
+
+//Navigation works straight off the wall grids the level colliders are built from
+impl LevelWallGrid {
+    //Agents are placed by their bottom left tile and take up size tiles from there
+    pub fn is_open(&self, coords: GridCoords, size: IVec2) -> bool {
+        for x in coords.x..coords.x + size.x {
+            for y in coords.y..coords.y + size.y {
+                let tile = GridCoords { x, y };
+                if !self.in_bounds(tile) || self.is_wall(tile) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    //Open, with a wall under at least part of the agent
+    pub fn is_standable(&self, coords: GridCoords, size: IVec2) -> bool {
+        self.is_open(coords, size)
+            && (coords.x..coords.x + size.x)
+                .map(|x| GridCoords { x, y: coords.y - 1 })
+                .any(|below| self.is_wall(below))
+    }
+
+    //The first place an agent would land if it fell from coords
+    pub fn landing(&self, coords: GridCoords, size: IVec2) -> Option<GridCoords> {
+        let mut tile = coords;
+        while self.is_open(tile, size) {
+            if self.is_standable(tile, size) {
+                return Some(tile);
+            }
+            tile.y -= 1;
+        }
+        None
+    }
+
+    pub fn agent_size(&self, half_size: Vec2) -> IVec2 {
+        IVec2::new(
+            ((half_size.x * 2.) / self.grid_size as f32).ceil().max(1.) as i32,
+            ((half_size.y * 2.) / self.grid_size as f32).ceil().max(1.) as i32,
+        )
+    }
+
+    pub fn to_grid(&self, position: Vec2, half_size: Vec2) -> GridCoords {
+        //Half a tile in so an agent exactly the size of a tile lands on the right one
+        let tile = (position - half_size - self.origin) / self.grid_size as f32 + Vec2::splat(0.5);
+        GridCoords {
+            x: tile.x.floor() as i32,
+            y: tile.y.floor() as i32,
+        }
+    }
+
+    //Where the center of an agent placed at coords would be
+    pub fn to_world(&self, coords: GridCoords, size: IVec2) -> Vec2 {
+        self.origin + (Vec2::new(coords.x as f32, coords.y as f32) + size.as_vec2() / 2.) * self.grid_size as f32
+    }
+}
+
+impl LevelWallGrids {
+    pub fn grid_at(&self, position: Vec2) -> Option<&LevelWallGrid> {
+        self.0.values().find(|grid| {
+            let tile = (position - grid.origin) / grid.grid_size as f32;
+            grid.in_bounds(GridCoords { x: tile.x.floor() as i32, y: tile.y.floor() as i32 })
+        })
+    }
+}
+
+//===============================================================
+
+//How an agent gets to a waypoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavMove {
+    Walk,
+    Fall,
+    Jump,
+    Fly,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub enum NavAgent {
+    //Jumps are in tiles
+    Ground { jump_height: i32, jump_distance: i32 },
+    Flying,
+}
+
+//Set a goal every frame something should be navigated to. Without one the agent is left alone
+#[derive(Component, Clone)]
+pub struct NavPath {
+    pub goal: Option<Vec2>,
+    pub waypoints: VecDeque<(Vec2, NavMove)>,
+    //The goal tile the waypoints lead to. Paths are only found again once the goal moves off it
+    pub path_goal: Option<GridCoords>,
+    pub repath_timer: Timer,
+}
+impl Default for NavPath {
+    fn default() -> Self {
+        NavPath {
+            goal: None,
+            waypoints: VecDeque::new(),
+            path_goal: None,
+            repath_timer: Timer::from_seconds(NAV_REPATH_TIME, false),
+        }
+    }
+}
+
+//===============================================================
+
+//Points either kind of agent straight at a target
+pub fn steer_towards(position: Vec2, target: Vec2, move_dir: Option<Mut<MoveDir>>, full_move_dir: Option<Mut<FullMoveDir>>) {
+    let direction = target - position;
+
+    if let Some(mut move_dir) = move_dir {
+        move_dir.0 = if direction.x > 0. { 1. } else if direction.x < 0. { -1. } else { 0. };
+    }
+    if let Some(mut full_move_dir) = full_move_dir {
+        full_move_dir.0 = if direction != Vec2::ZERO { direction.normalize() } else { Vec2::ZERO };
+    }
+}
+
+//===============================================================
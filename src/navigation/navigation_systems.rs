@@ -0,0 +1,357 @@
+//===============================================================
+
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap}};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use heron::prelude::*;
+
+use super::navigation_components::*;
+
+use crate::{
+    world::{LevelWallGrid, LevelWallGrids},
+    physics::physics_components::{MoveDir, FullMoveDir, CanJump, IsGrounded},
+    general::general_components::FrameDelta,
+};
+
+//===============================================================
+
+//Every tile an agent could move to from a tile, with how it gets there and what it costs
+fn neighbours(grid: &LevelWallGrid, coords: GridCoords, size: IVec2, agent: NavAgent) -> Vec<(GridCoords, NavMove, i32)> {
+    let mut neighbours = Vec::new();
+
+    match agent {
+        NavAgent::Flying => {
+            for x in -1..=1 {
+                for y in -1..=1 {
+                    if x == 0 && y == 0 {
+                        continue;
+                    }
+                    let next = GridCoords { x: coords.x + x, y: coords.y + y };
+                    if !grid.is_open(next, size) {
+                        continue;
+                    }
+
+                    //Don't cut corners, the agent would catch on them
+                    if x != 0 && y != 0 {
+                        let side_x = GridCoords { x: coords.x + x, y: coords.y };
+                        let side_y = GridCoords { x: coords.x, y: coords.y + y };
+                        if !grid.is_open(side_x, size) || !grid.is_open(side_y, size) {
+                            continue;
+                        }
+                        neighbours.push((next, NavMove::Fly, 14));
+                    }
+                    else {
+                        neighbours.push((next, NavMove::Fly, 10));
+                    }
+                }
+            }
+        },
+        NavAgent::Ground { jump_height, jump_distance } => {
+            for direction in [-1, 1] {
+
+                //Walk along the ground or off the edge of it
+                let side = GridCoords { x: coords.x + direction, y: coords.y };
+                if grid.is_open(side, size) {
+                    if grid.is_standable(side, size) {
+                        neighbours.push((side, NavMove::Walk, 10));
+                    }
+                    else if let Some(landing) = grid.landing(side, size) {
+                        neighbours.push((landing, NavMove::Fall, 10 + 5 * (coords.y - landing.y)));
+                    }
+                }
+
+                for height in 0..=jump_height {
+                    for distance in 1..=jump_distance {
+                        //Already covered by walking
+                        if height == 0 && distance == 1 {
+                            continue;
+                        }
+
+                        let target = GridCoords { x: coords.x + direction * distance, y: coords.y + height };
+                        if grid.is_standable(target, size) && jump_is_clear(grid, coords, target, size) {
+                            neighbours.push((target, NavMove::Jump, 10 * distance + 15 * height + 10));
+                        }
+                    }
+                }
+            }
+        },
+    }
+
+    neighbours
+}
+
+//Jumps go straight up, across and then down, with a tile of headroom over the top if there is space
+fn jump_is_clear(grid: &LevelWallGrid, from: GridCoords, to: GridCoords, size: IVec2) -> bool {
+    let direction = (to.x - from.x).signum();
+    let highest = from.y.max(to.y);
+
+    [highest + 1, highest].iter().any(|&top| {
+        let rise = (from.y..=top).all(|y| grid.is_open(GridCoords { x: from.x, y }, size));
+        let across = (0..=(to.x - from.x).abs()).all(|x| grid.is_open(GridCoords { x: from.x + x * direction, y: top }, size));
+        let fall = (to.y..=top).all(|y| grid.is_open(GridCoords { x: to.x, y }, size));
+
+        rise && across && fall
+    })
+}
+
+fn heuristic(from: GridCoords, to: GridCoords) -> i32 {
+    let dx = (from.x - to.x).abs();
+    let dy = (from.y - to.y).abs();
+    10 * (dx + dy) - 6 * dx.min(dy)
+}
+
+//A* from start to goal. If the goal can't be reached, the path leads to the closest tile that can be
+fn find_path(grid: &LevelWallGrid, start: GridCoords, goal: GridCoords, size: IVec2, agent: NavAgent) -> Vec<(GridCoords, NavMove)> {
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<GridCoords, (GridCoords, NavMove)> = HashMap::new();
+    let mut costs: HashMap<GridCoords, i32> = HashMap::new();
+
+    let mut closest = (heuristic(start, goal), start);
+    let mut searched = 0;
+
+    open.push(Reverse((heuristic(start, goal), start.x, start.y)));
+    costs.insert(start, 0);
+
+    while let Some(Reverse((_, x, y))) = open.pop() {
+        let current = GridCoords { x, y };
+        if current == goal {
+            closest = (0, current);
+            break;
+        }
+
+        searched += 1;
+        if searched > NAV_MAX_SEARCH_NODES {
+            break;
+        }
+
+        let current_cost = costs[&current];
+        for (next, nav_move, move_cost) in neighbours(grid, current, size, agent) {
+
+            let next_cost = current_cost + move_cost;
+            if costs.get(&next).is_some_and(|&cost| cost <= next_cost) {
+                continue;
+            }
+
+            costs.insert(next, next_cost);
+            came_from.insert(next, (current, nav_move));
+
+            let distance = heuristic(next, goal);
+            if distance < closest.0 {
+                closest = (distance, next);
+            }
+            open.push(Reverse((next_cost + distance, next.x, next.y)));
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut current = closest.1;
+    while let Some(&(previous, nav_move)) = came_from.get(&current) {
+        path.push((current, nav_move));
+        current = previous;
+    }
+    path.reverse();
+    path
+}
+
+//===============================================================
+
+fn half_size(shape: &CollisionShape) -> Vec2 {
+    match shape {
+        CollisionShape::Cuboid { half_extends, .. } => half_extends.truncate(),
+        _ => Vec2::ZERO,
+    }
+}
+
+fn waypoint_reached(position: Vec2, waypoint: Vec2, agent: NavAgent, grid_size: f32) -> bool {
+    match agent {
+        NavAgent::Flying => position.distance(waypoint) < NAV_WAYPOINT_REACHED,
+        //Ground agents can only steer sideways, so height only has to be close
+        NavAgent::Ground { .. } => {
+            (position.x - waypoint.x).abs() < NAV_WAYPOINT_REACHED && (position.y - waypoint.y).abs() < grid_size
+        },
+    }
+}
+
+//Finds a path to the goal of each agent every so often and steers along it
+pub fn navigate_agents(
+    mut agent_query: Query<(
+        &mut NavPath, &NavAgent, &GlobalTransform, &CollisionShape,
+        Option<&mut MoveDir>, Option<&mut FullMoveDir>, Option<&mut CanJump>, Option<&IsGrounded>,
+    )>,
+    wall_grids: Res<LevelWallGrids>,
+    time: Res<FrameDelta>,
+) {
+    for (mut path, agent, transform, shape, move_dir, full_move_dir, can_jump, grounded) in agent_query.iter_mut() {
+
+        let goal = match path.goal.take() {
+            Some(goal) => goal,
+            None => {
+                path.waypoints.clear();
+                path.path_goal = None;
+                continue;
+            },
+        };
+
+        let position = transform.translation.truncate();
+        let half_size = half_size(shape);
+
+        //Outside of any loaded level, just head straight for the goal
+        let grid = match wall_grids.grid_at(position) {
+            Some(grid) => grid,
+            None => {
+                steer_towards(position, goal, move_dir, full_move_dir);
+                continue;
+            },
+        };
+        let size = grid.agent_size(half_size);
+
+        //Only find a new path once the goal has moved to another tile, at most every NAV_REPATH_TIME
+        let mut goal_tile = grid.to_grid(goal, half_size);
+        path.repath_timer.tick(time.delta());
+        if path.path_goal.is_none() || (path.repath_timer.finished() && path.path_goal != Some(goal_tile)) {
+            path.repath_timer.reset();
+            path.path_goal = Some(goal_tile);
+
+            let mut start = grid.to_grid(position, half_size);
+
+            //Ground agents path between the spots they'd land on
+            if let NavAgent::Ground { .. } = agent {
+                start = grid.landing(start, size).unwrap_or(start);
+                goal_tile = grid.landing(goal_tile, size).unwrap_or(goal_tile);
+            }
+
+            path.waypoints = find_path(grid, start, goal_tile, size, *agent)
+                .into_iter()
+                .map(|(coords, nav_move)| (grid.to_world(coords, size), nav_move))
+                .collect();
+        }
+
+        while let Some(&(waypoint, _)) = path.waypoints.front() {
+            if !waypoint_reached(position, waypoint, *agent, grid.grid_size as f32) {
+                break;
+            }
+            path.waypoints.pop_front();
+        }
+
+        let (target, nav_move) = match path.waypoints.front() {
+            Some(&waypoint) => waypoint,
+            //Close the last bit of distance to the goal directly
+            None => (goal, NavMove::Walk),
+        };
+
+        steer_towards(position, target, move_dir, full_move_dir);
+
+        //Hold jump until the agent is level with where it's jumping to
+        if let Some(mut can_jump) = can_jump {
+            let grounded = grounded.is_some_and(|grounded| grounded.grounded);
+            let jump = nav_move == NavMove::Jump && position.y < target.y + grid.grid_size as f32 / 2.;
+
+            can_jump.jump_repressed = jump && grounded;
+            can_jump.jump_pressed = jump;
+        }
+    }
+}
+
+//===============================================================
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const SIZE: IVec2 = IVec2::ONE;
+    const GROUND: NavAgent = NavAgent::Ground { jump_height: 2, jump_distance: 3 };
+
+    //A 12x8 room with a floor along the bottom row
+    fn room(extra_walls: &[(i32, i32)]) -> LevelWallGrid {
+        let mut walls: HashSet<GridCoords> = (0..12).map(|x| GridCoords { x, y: 0 }).collect();
+        walls.extend(extra_walls.iter().map(|&(x, y)| GridCoords { x, y }));
+
+        LevelWallGrid {
+            origin: Vec2::ZERO,
+            grid_size: 16,
+            width: 12,
+            height: 8,
+            walls,
+        }
+    }
+
+    fn column(x: i32, from_y: i32, to_y: i32) -> Vec<(i32, i32)> {
+        (from_y..=to_y).map(|y| (x, y)).collect()
+    }
+
+    #[test]
+    fn walks_along_the_floor() {
+        let grid = room(&[]);
+        let path = find_path(&grid, GridCoords { x: 1, y: 1 }, GridCoords { x: 5, y: 1 }, SIZE, GROUND);
+
+        assert_eq!(path.last().unwrap().0, GridCoords { x: 5, y: 1 });
+        assert!(path.iter().all(|&(_, nav_move)| nav_move == NavMove::Walk));
+    }
+
+    #[test]
+    fn unreachable_goal_leads_to_closest_tile() {
+        //A wall from floor to ceiling cuts the room in two
+        let grid = room(&column(6, 1, 7));
+        let path = find_path(&grid, GridCoords { x: 1, y: 1 }, GridCoords { x: 9, y: 1 }, SIZE, NavAgent::Flying);
+
+        assert_eq!(path.last().unwrap().0, GridCoords { x: 5, y: 1 });
+    }
+
+    #[test]
+    fn ground_agents_jump_over_low_walls() {
+        let grid = room(&column(5, 1, 2));
+        let path = find_path(&grid, GridCoords { x: 1, y: 1 }, GridCoords { x: 9, y: 1 }, SIZE, GROUND);
+
+        assert_eq!(path.last().unwrap().0, GridCoords { x: 9, y: 1 });
+        assert!(path.iter().any(|&(_, nav_move)| nav_move == NavMove::Jump));
+    }
+
+    #[test]
+    fn ground_agents_cant_jump_over_high_walls() {
+        let grid = room(&column(5, 1, 4));
+        let path = find_path(&grid, GridCoords { x: 1, y: 1 }, GridCoords { x: 9, y: 1 }, SIZE, GROUND);
+
+        assert_ne!(path.last().map(|&(coords, _)| coords), Some(GridCoords { x: 9, y: 1 }));
+    }
+
+    #[test]
+    fn flying_agents_go_where_ground_agents_cant() {
+        //A ledge high above the floor with nothing to climb up by
+        let mut walls = column(5, 1, 4);
+        walls.extend([(8, 5), (9, 5), (10, 5)]);
+        let grid = room(&walls);
+        let goal = GridCoords { x: 9, y: 6 };
+
+        let flying = find_path(&grid, GridCoords { x: 1, y: 1 }, goal, SIZE, NavAgent::Flying);
+        assert_eq!(flying.last().unwrap().0, goal);
+        assert!(flying.iter().all(|&(_, nav_move)| nav_move == NavMove::Fly));
+
+        let ground = find_path(&grid, GridCoords { x: 1, y: 1 }, goal, SIZE, GROUND);
+        assert_ne!(ground.last().map(|&(coords, _)| coords), Some(goal));
+    }
+
+    #[test]
+    fn jumps_are_limited_by_distance() {
+        //A bottomless pit five tiles wide
+        let mut grid = room(&[]);
+        for x in 3..8 {
+            grid.walls.remove(&GridCoords { x, y: 0 });
+        }
+        let start = GridCoords { x: 1, y: 1 };
+        let goal = GridCoords { x: 9, y: 1 };
+
+        let path = find_path(&grid, start, goal, SIZE, GROUND);
+        assert_ne!(path.last().map(|&(coords, _)| coords), Some(goal));
+
+        let long_jump = NavAgent::Ground { jump_height: 2, jump_distance: 6 };
+        let path = find_path(&grid, start, goal, SIZE, long_jump);
+        assert_eq!(path.last().unwrap().0, goal);
+        assert!(path.iter().any(|&(_, nav_move)| nav_move == NavMove::Jump));
+    }
+}
+
+//===============================================================
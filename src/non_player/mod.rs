@@ -3,9 +3,12 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::RegisterLdtkObjects;
 
+use crate::general::GameState;
+
 pub mod non_player_components;
 mod non_player_systems;
 pub mod non_player_prefabs;
+pub mod non_player_definitions;
 
 //===============================================================
 
@@ -15,14 +18,35 @@ impl Plugin for NonPlayerPlugin {
 
         app
 
-            //.register_ldtk_entity::<non_player_prefabs::FoxBundle>("Fox")
-            .register_ldtk_entity::<non_player_prefabs::BatBundle>("Bat")
-
-            .add_system(non_player_systems::enemy_target_player)
-            .add_system(non_player_systems::enemy_attack_target)
-            .add_system(non_player_systems::enemy_idle)
-
-            .add_system(non_player_systems::enemy_damage)
+            .add_asset::<non_player_definitions::EnemyDefinition>()
+            .init_asset_loader::<non_player_definitions::EnemyDefinitionLoader>()
+
+            .register_ldtk_entity::<non_player_prefabs::EnemyBundle>("Bat")
+            .register_ldtk_entity::<non_player_prefabs::EnemyBundle>("Fox")
+            .register_ldtk_entity::<non_player_prefabs::EnemyBundle>("Enemy")
+
+            .insert_resource(non_player_components::DefeatedEnemies::default())
+            .add_system(non_player_systems::reload_enemy_definitions)
+
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(non_player_systems::build_enemies)
+                    .with_system(non_player_systems::enemy_target_player)
+                    .with_system(non_player_systems::enemy_attack_target.label("EnemyBehaviour"))
+                    .with_system(non_player_systems::enemy_wander.label("MovementInput"))
+                    .with_system(non_player_systems::enemy_search.label("EnemyBehaviour"))
+                    .with_system(non_player_systems::enemy_flee_player.label("MovementInput"))
+                    .with_system(non_player_systems::enemy_flee_target.label("MovementInput"))
+
+                    .with_system(non_player_systems::enemy_use_weapons)
+                    .with_system(non_player_systems::enemy_damage)
+
+                    //Needs the enemy's components, which are gone once it's despawned
+                    .with_system(non_player_systems::record_defeated_enemies.after("ChangeHealth").before("ResolveDeath"))
+                    .with_system(non_player_systems::respawn_enemies_on_room_entry)
+            )
+            //Retrying happens from the game over menu
+            .add_system(non_player_systems::respawn_enemies_on_rest)
         ;
 
     }
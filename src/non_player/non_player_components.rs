@@ -1,5 +1,7 @@
 //===============================================================
 
+use std::collections::{HashMap, HashSet};
+
 use bevy::prelude::*;
 use heron::Velocity;
 
@@ -8,10 +10,27 @@ use crate::{
         SpriteSheetAnimation, AutoAnimation
     }, 
     physics::physics_components::{
-        ColliderBundle, MovementBundle, MaxVelocity, Accel, FullMoveDir, SetGravityScale,
+        ColliderBundle, MovementBundle, MaxVelocity, Accel, FullMoveDir, SetGravityScale, IsOnWall,
     },
+    general::general_components::{EntityIid, Damage},
 };
 
+use super::{non_player_prefabs::EnemySpawner, non_player_definitions::EnemyRespawn};
+
+//===============================================================
+
+pub const ENEMY_TURN_COOLDOWN:      f32 = 0.4;
+pub const ENEMY_LEDGE_CHECK_DEPTH:  f32 = 8.;
+
+pub const ENEMY_DRIFT_RADIUS:       f32 = 48.;
+pub const ENEMY_DRIFT_TIME:         f32 = 2.5;
+pub const ENEMY_DRIFT_REACHED:      f32 = 4.;
+
+pub const ENEMY_SEARCH_TIME:        f32 = 4.;
+pub const ENEMY_SEARCH_REACHED:     f32 = 16.;
+
+pub const ENEMY_CONTACT_KNOCKBACK:  f32 = 300.;
+
 //===============================================================
 
 #[derive(Clone, Default, Bundle)]
@@ -25,6 +44,7 @@ pub struct NonPlayerBundle {
     pub physics:    ColliderBundle,
     #[bundle]
     pub movement:   MovementBundle,
+    pub on_wall:    IsOnWall,
 }
 
 #[derive(Clone, Default, Bundle)]
@@ -51,40 +71,124 @@ pub struct NonPlayerFlyingBundle {
 pub struct NonPlayer(pub i32);
 
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Default)]
 pub enum NonPlayerPassiveState {
+    #[default]
     Wander,
     Flee {target: Entity},
 }
-impl Default for NonPlayerPassiveState {
-    fn default() -> Self {
-        NonPlayerPassiveState::Wander
-    }
-}
 
 
-#[derive(Component, Clone, PartialEq, Eq)]
+#[derive(Component, Clone, PartialEq, Default)]
 pub enum NonPlayerAggressiveState {
+    #[default]
     Wander,
-    Search,
+    Search {last_seen: Vec2, time_left: f32},
     Attack {target: Entity},
 }
-impl Default for NonPlayerAggressiveState {
-    fn default() -> Self {
-        NonPlayerAggressiveState::Wander
-    }
-}
 
 
 #[derive(Component, Default, Clone)]
 pub struct NonPlayerAttackPlayer {
     pub attack_range: f32,
     pub lost_range: f32,
+    //In radians
+    pub vision_cone: Option<f32>,
+}
+
+#[derive(Component, Default, Clone)]
+pub struct NonPlayerFleePlayer {
+    pub flee_range: f32,
+    pub safe_range: f32,
+}
+
+//Ground enemies patrol back and forth, flying enemies drift around where they started
+#[derive(Component, Clone)]
+pub struct NonPlayerWander {
+    pub direction: f32,
+    pub turn_cooldown: Timer,
+
+    pub home: Option<Vec2>,
+    pub drift_target: Option<Vec2>,
+    pub drift_step: u32,
+    pub drift_timer: Timer,
+}
+impl Default for NonPlayerWander {
+    fn default() -> Self {
+        //Start finished so the first wall or ledge turns the enemy straight away
+        let mut turn_cooldown = Timer::from_seconds(ENEMY_TURN_COOLDOWN, false);
+        turn_cooldown.tick(turn_cooldown.duration());
+
+        NonPlayerWander {
+            direction: 1.,
+            turn_cooldown,
+            home: None,
+            drift_target: None,
+            drift_step: 0,
+            drift_timer: Timer::from_seconds(ENEMY_DRIFT_TIME, false),
+        }
+    }
+}
+impl NonPlayerWander {
+    //Steps around home with the golden angle instead of using an rng, so replays stay deterministic
+    pub fn next_drift_target(&mut self) -> Option<Vec2> {
+        let home = self.home?;
+
+        self.drift_step += 1;
+        let angle = self.drift_step as f32 * 2.399_963;
+        let distance = ENEMY_DRIFT_RADIUS * (0.5 + 0.5 * (self.drift_step as f32 * 0.618_034).fract());
+
+        self.drift_target = Some(home + Vec2::new(angle.cos(), angle.sin()) * distance);
+        self.drift_target
+    }
 }
 
 //===============================================================
 
 #[derive(Component, Default, Clone)]
-pub struct NonPlayerDamage (pub i32);
+pub struct NonPlayerDamage (pub Damage);
 
-//===============================================================
\ No newline at end of file
+//Lets an enemy use one of the weapons in its inventory. Lives on the weapon entity
+#[derive(Component, Clone)]
+pub struct NonPlayerWeapon {
+    pub range: f32,
+    pub charge: Timer,
+    pub cooldown: Timer,
+}
+impl NonPlayerWeapon {
+    pub fn new(range: f32, charge_time: f32, cooldown: f32) -> Self {
+        NonPlayerWeapon {
+            range,
+            charge: Timer::from_seconds(charge_time, false),
+            //Starts cooling down so the player gets a moment before the first attack
+            cooldown: Timer::from_seconds(cooldown, false),
+        }
+    }
+}
+
+//===============================================================
+
+//Everything needed to put a defeated enemy back where it was placed
+#[derive(Clone)]
+pub struct DefeatedEnemy {
+    pub respawn: EnemyRespawn,
+    pub spawner: EnemySpawner,
+    pub spawn_point: Transform,
+    pub parent: Option<Entity>,
+}
+
+//Enemies that have been defeated, by LDtk iid. Enemies in here are removed as soon as
+//LDtk spawns them again, until their respawn rule brings them back
+#[derive(Default)]
+pub struct DefeatedEnemies {
+    pub respawning: HashMap<EntityIid, DefeatedEnemy>,
+    //Enemies that never respawn. These are kept in the save file
+    pub permanent: HashSet<EntityIid>,
+}
+impl DefeatedEnemies {
+    pub fn is_defeated(&self, iid: &EntityIid) -> bool {
+        self.respawning.contains_key(iid) || self.permanent.contains(iid)
+    }
+}
+
+//===============================================================
@@ -0,0 +1,361 @@
+//===============================================================
+
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use heron::Velocity;
+use serde::Deserialize;
+
+use crate::{
+    animation::animation_components::{
+        SpriteSheetAnimation, AnimationDefinition, AutoAnimation
+    },
+    physics::physics_components::{
+        ColliderBundle, MovementBundle, MaxVelocity, Accel, FullMoveDir, SetGravityScale, IsOnWall, CanJump,
+    },
+    navigation::navigation_components::{NavAgent, NavPath},
+    weapons::{weapon_components::{WeaponInventory, WeaponInventoryBundle}, weapon_prefabs::WeaponSpawnerBundle},
+    general::general_components::{Health, HitStun, Damage, DamageType, Resistances, StatusEffect, StatusEffects},
+    boss::boss_components::{Boss, BossDefinition},
+};
+
+use super::non_player_components::*;
+
+//===============================================================
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum EnemyMovement {
+    Ground,
+    Flying,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum EnemyBehaviour {
+    Passive {
+        flee_range: f32,
+        safe_range: f32,
+    },
+    Aggressive {
+        attack_range: f32,
+        lost_range: f32,
+        //Width of the view cone in degrees, in the direction the enemy is moving.
+        //Without one the enemy can see all around it
+        #[serde(default)]
+        vision_cone: Option<f32>,
+    },
+}
+
+//Lets a ground enemy jump, and tells navigation how far it can get
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnemyJump {
+    //In tiles
+    pub height: i32,
+    pub distance: i32,
+
+    pub time: f32,
+    pub force: f32,
+    pub initial_force: f32,
+}
+
+//A weapon the enemy spawns with, using the same ids weapons are equipped by
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnemyWeapon {
+    pub id: String,
+    //The target has to be this close before the weapon is used
+    pub range: f32,
+    //How long the weapon is charged before it's released
+    pub charge_time: f32,
+    pub cooldown: f32,
+}
+
+//When a defeated enemy comes back
+#[derive(Deserialize, Component, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum EnemyRespawn {
+    //Once the player leaves the room it was defeated in
+    #[default]
+    OnRoomEntry,
+    //When the player rests at a checkpoint or retries after dying
+    OnRest,
+    //Bosses and minibosses stay defeated, even between sessions
+    Never,
+}
+impl EnemyRespawn {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "OnRoomEntry" => Some(EnemyRespawn::OnRoomEntry),
+            "OnRest" => Some(EnemyRespawn::OnRest),
+            "Never" => Some(EnemyRespawn::Never),
+            _ => None,
+        }
+    }
+}
+
+//Everything needed to build an enemy. Loaded from assets/enemies/*.enemy.ron
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "5b0a3f0e-8f0c-4b7e-9a4f-6f1f2d8c1e42"]
+pub struct EnemyDefinition {
+    pub name: String,
+    pub movement: EnemyMovement,
+    pub behaviour: EnemyBehaviour,
+
+    pub health: i32,
+    pub iframes: f32,
+    pub contact_damage: Option<i32>,
+    #[serde(default)]
+    pub contact_damage_type: DamageType,
+    #[serde(default)]
+    pub contact_status: Option<StatusEffect>,
+    //Damage type -> fraction of it ignored
+    #[serde(default)]
+    pub resistances: HashMap<DamageType, f32>,
+    #[serde(default)]
+    pub respawn: EnemyRespawn,
+    #[serde(default)]
+    pub jump: Option<EnemyJump>,
+    //Only the first two are used, one for each inventory slot
+    #[serde(default)]
+    pub weapons: Vec<EnemyWeapon>,
+    #[serde(default)]
+    pub boss: Option<BossDefinition>,
+
+    pub size: Vec2,
+    pub max_speed: Vec2,
+    pub acceleration: f32,
+    pub deacceleration: f32,
+
+    //The first animation is the one the enemy starts with
+    pub animations: Vec<AnimationDefinition>,
+}
+impl EnemyDefinition {
+    pub fn asset_path(enemy_type: &str) -> String {
+        format!("enemies/{}.enemy.ron", enemy_type.to_lowercase())
+    }
+
+    fn create_animation(
+        &self,
+        assets: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> (SpriteSheetAnimation, Handle<TextureAtlas>) {
+
+        let mut sprite_sheet_animation = SpriteSheetAnimation::default();
+        let mut starting_atlas = Handle::default();
+
+        for (index, definition) in self.animations.iter().enumerate() {
+
+            let atlas_handle = definition.load_texture_atlas(assets, texture_atlases);
+            let animation = definition.create_animation(atlas_handle.clone());
+
+            if index == 0 {
+                sprite_sheet_animation = SpriteSheetAnimation::new(
+                    definition.animation_type.clone(),
+                    animation,
+                    false,
+                );
+                starting_atlas = atlas_handle;
+            }
+            else {
+                sprite_sheet_animation.add_animation(definition.animation_type.clone(), animation);
+            }
+        }
+
+        (sprite_sheet_animation, starting_atlas)
+    }
+
+    fn create_jump(&self) -> CanJump {
+        match &self.jump {
+            Some(jump) => CanJump {
+                jumps_left: 1,
+                total_jumps: 1,
+                ..CanJump::new(jump.time, jump.force, jump.initial_force)
+            },
+            None => CanJump::default(),
+        }
+    }
+
+    //Inserts all the components described by this definition onto an already spawned entity
+    pub fn insert_components(
+        &self,
+        entity: &mut bevy::ecs::system::EntityCommands,
+        level_uid: i32,
+        transform: Transform,
+        assets: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) {
+        let (animation, texture_atlas) = self.create_animation(assets, texture_atlases);
+
+        let sprite = SpriteSheetBundle {
+            texture_atlas,
+            transform,
+            ..Default::default()
+        };
+        let max_velocity = MaxVelocity {
+            x: self.max_speed.x,
+            y: self.max_speed.y,
+        };
+        let acceleration = Accel {
+            accel:          self.acceleration,
+            deaccel:        self.deacceleration,
+            air_deaccel:    None,
+        };
+
+        match self.movement {
+            EnemyMovement::Ground => {
+                entity.insert_bundle(NonPlayerBundle {
+                    non_player: NonPlayer(level_uid),
+                    sprite,
+                    animation,
+                    auto_anim: AutoAnimation::default(),
+                    physics: ColliderBundle::non_player(self.size.x, self.size.y),
+                    movement: MovementBundle {
+                        max_velocity,
+                        acceleration,
+                        jump: self.create_jump(),
+                        ..Default::default()
+                    },
+                    on_wall: IsOnWall::default(),
+                });
+                entity.insert(NavAgent::Ground {
+                    jump_height: self.jump.as_ref().map_or(0, |jump| jump.height),
+                    jump_distance: self.jump.as_ref().map_or(0, |jump| jump.distance),
+                });
+            },
+            EnemyMovement::Flying => {
+                entity.insert_bundle(NonPlayerFlyingBundle {
+                    non_player: NonPlayer(level_uid),
+                    sprite,
+                    animation,
+                    auto_anim: AutoAnimation::default(),
+                    physics: ColliderBundle::non_player(self.size.x, self.size.y),
+                    move_dir: FullMoveDir::default(),
+                    max_velocity,
+                    acceleration,
+                    velocity: Velocity::default(),
+                    gravity: SetGravityScale {
+                        scale: 0.,
+                        reset_velocity: false,
+                    },
+                });
+                entity.insert(NavAgent::Flying);
+            },
+        }
+
+        entity
+            .insert(Name::new(self.name.clone()))
+            .insert(Health::new(self.health, self.iframes))
+            .insert(HitStun::default())
+            .insert(NonPlayerWander::default())
+            .insert(NavPath::default());
+
+        match self.behaviour {
+            EnemyBehaviour::Passive { flee_range, safe_range } => {
+                entity
+                    .insert(NonPlayerPassiveState::default())
+                    .insert(NonPlayerFleePlayer {
+                        flee_range,
+                        safe_range,
+                    });
+            },
+            EnemyBehaviour::Aggressive { attack_range, lost_range, vision_cone } => {
+                entity
+                    .insert(NonPlayerAggressiveState::default())
+                    .insert(NonPlayerAttackPlayer {
+                        attack_range,
+                        lost_range,
+                        vision_cone: vision_cone.map(f32::to_radians),
+                    });
+            },
+        }
+
+        if let Some(damage) = self.contact_damage {
+            entity.insert(NonPlayerDamage(Damage {
+                amount: damage,
+                damage_type: self.contact_damage_type,
+                status: self.contact_status,
+            }));
+        }
+        if !self.resistances.is_empty() {
+            entity.insert(Resistances(self.resistances.clone()));
+        }
+
+        if !self.weapons.is_empty() {
+            insert_enemy_weapons(entity, &self.weapons, assets);
+        }
+
+        if let Some(boss) = &self.boss {
+            entity.insert(Boss::new(self.name.clone(), boss));
+        }
+    }
+
+    //Used when hot reloading so an enemy doesn't end up with components from its old definition
+    pub fn remove_components(entity: &mut bevy::ecs::system::EntityCommands) {
+        entity
+            .remove_bundle::<NonPlayerBundle>()
+            .remove_bundle::<NonPlayerFlyingBundle>()
+            .remove::<Name>()
+            .remove::<Health>()
+            .remove::<HitStun>()
+            .remove::<NonPlayerPassiveState>()
+            .remove::<NonPlayerAggressiveState>()
+            .remove::<NonPlayerAttackPlayer>()
+            .remove::<NonPlayerFleePlayer>()
+            .remove::<NonPlayerWander>()
+            .remove::<NavAgent>()
+            .remove::<NavPath>()
+            .remove::<NonPlayerDamage>()
+            .remove::<Resistances>()
+            .remove::<StatusEffects>()
+            .remove_bundle::<WeaponInventoryBundle>()
+            .remove::<Boss>();
+    }
+}
+
+//Spawns the weapons as children and fills a new inventory with them. Only the first two fit
+pub fn insert_enemy_weapons(
+    entity: &mut bevy::ecs::system::EntityCommands,
+    weapons: &[EnemyWeapon],
+    assets: &AssetServer,
+) {
+    let mut inventory = WeaponInventory::default();
+    entity.with_children(|parent| {
+        for weapon in weapons.iter().take(2) {
+            let weapon_entity = parent
+                .spawn_bundle(WeaponSpawnerBundle::new(&weapon.id, assets, false))
+                .insert(NonPlayerWeapon::new(weapon.range, weapon.charge_time, weapon.cooldown))
+                .id();
+            inventory.add_weapon(weapon_entity);
+        }
+    });
+    entity.insert_bundle(WeaponInventoryBundle {
+        inventory,
+        ..Default::default()
+    });
+}
+
+//===============================================================
+
+#[derive(Default)]
+pub struct EnemyDefinitionLoader;
+impl AssetLoader for EnemyDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let definition: EnemyDefinition = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(definition));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["enemy.ron"]
+    }
+}
+
+//===============================================================
@@ -1,103 +1,475 @@
 //===============================================================
 
 use bevy::prelude::*;
-use heron::CollisionEvent;
+use heron::{CollisionEvent, CollisionShape, CollisionLayers, rapier_plugin::PhysicsWorld};
+use bevy_ecs_ldtk::prelude::LevelSelection;
 
-use crate::{player::player_components::Player, physics::physics_components::{MoveDir, FullMoveDir, CollisionLayer}, general::general_components::{HealthChangeEvent, HealthChangeType}};
+use crate::{
+    player::player_components::Player,
+    physics::physics_components::{MoveDir, FullMoveDir, CollisionLayer, IsGrounded, IsOnWall},
+    general::general_components::{HealthChangeEvent, HealthChangeType, Knockback, EntityDiedEvent, EntityIid, FrameDelta},
+    world::{LevelChangedEvent, CheckpointActivatedEvent, RetryEvent},
+    navigation::navigation_components::{NavPath, steer_towards},
+    weapons::weapon_components::{WeaponInventory, WeaponState, WeaponDirection, WeaponDirections, WeaponCharge},
+};
 
-use super::non_player_components::*;
+use super::{
+    non_player_components::*,
+    non_player_definitions::{EnemyDefinition, EnemyRespawn},
+    non_player_prefabs::{EnemySpawner, EnemyBuilt, EnemySpawnPoint},
+};
 
 //===============================================================
 
+pub fn build_enemies(
+    spawner_query: Query<(Entity, &EnemySpawner, &EntityIid, &Transform, Option<&EnemySpawnPoint>), Without<EnemyBuilt>>,
+    definitions: Res<Assets<EnemyDefinition>>,
+    defeated: Res<DefeatedEnemies>,
+    assets: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut commands: Commands,
+) {
+    for (entity, spawner, iid, transform, spawn_point) in spawner_query.iter() {
+
+        //LDtk spawns every enemy again when a level reloads
+        if defeated.is_defeated(iid) {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        if let Some(definition) = definitions.get(&spawner.definition) {
+
+            let mut entity_commands = commands.entity(entity);
+            definition.insert_components(
+                &mut entity_commands,
+                spawner.level_uid,
+                *transform,
+                &assets,
+                &mut texture_atlases,
+            );
+            //Beaten bosses stay beaten, whatever the level says
+            let respawn = match definition.boss {
+                Some(_) => EnemyRespawn::Never,
+                None => spawner.respawn.unwrap_or(definition.respawn),
+            };
+            entity_commands
+                .insert(respawn)
+                .insert(EnemyBuilt);
+
+            if spawn_point.is_none() {
+                entity_commands.insert(EnemySpawnPoint(*transform));
+            }
+        }
+    }
+}
+
+//Rebuild enemies when their definition file is changed
+pub fn reload_enemy_definitions(
+    mut definition_events: EventReader<AssetEvent<EnemyDefinition>>,
+    spawner_query: Query<(Entity, &EnemySpawner, &Transform, Option<&WeaponInventory>), With<EnemyBuilt>>,
+    mut commands: Commands,
+) {
+    for event in definition_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            for (entity, spawner, transform, weapons) in spawner_query.iter() {
+                if spawner.definition != *handle {
+                    continue;
+                }
+
+                //The new definition spawns its own weapons
+                if let Some(weapons) = weapons {
+                    for slot in [weapons.get_slot1(), weapons.get_slot2()] {
+                        if let Some(weapon) = *slot {
+                            commands.entity(weapon).despawn_recursive();
+                        }
+                    }
+                }
+
+                let mut entity_commands = commands.entity(entity);
+                EnemyDefinition::remove_components(&mut entity_commands);
+                entity_commands
+                    .insert(*transform)
+                    .remove::<EnemyBuilt>();
+            }
+        }
+    }
+}
+
+//===============================================================
+
+pub fn record_defeated_enemies(
+    enemy_query: Query<(&EnemySpawner, &EntityIid, &EnemySpawnPoint, &EnemyRespawn, Option<&Parent>)>,
+    mut died_event: EventReader<EntityDiedEvent>,
+    mut defeated: ResMut<DefeatedEnemies>,
+) {
+    for event in died_event.iter() {
+        if let Ok((spawner, iid, spawn_point, respawn, parent)) = enemy_query.get(event.0) {
+
+            if *respawn == EnemyRespawn::Never {
+                defeated.permanent.insert(iid.clone());
+                continue;
+            }
+
+            defeated.respawning.insert(iid.clone(), DefeatedEnemy {
+                respawn: *respawn,
+                spawner: spawner.clone(),
+                spawn_point: spawn_point.0,
+                parent: parent.map(|parent| parent.0),
+            });
+        }
+    }
+}
+
+//Enemies in levels that are still loaded have to be spawned again by hand. Levels that
+//aren't loaded will spawn their enemies themselves once they no longer count as defeated
+fn respawn_enemy(
+    iid: EntityIid,
+    enemy: DefeatedEnemy,
+    parent_query: &Query<(), With<GlobalTransform>>,
+    commands: &mut Commands,
+) {
+    let parent = match enemy.parent {
+        Some(parent) if parent_query.get(parent).is_ok() => parent,
+        _ => return,
+    };
+
+    let respawned = commands
+        .spawn()
+        .insert(enemy.spawner)
+        .insert(iid)
+        .insert(EnemySpawnPoint(enemy.spawn_point))
+        .insert(enemy.spawn_point)
+        .insert(GlobalTransform::default())
+        .id();
+    commands.entity(parent).add_child(respawned);
+}
+
+pub fn respawn_enemies_on_room_entry(
+    parent_query: Query<(), With<GlobalTransform>>,
+    mut level_changed_event: EventReader<LevelChangedEvent>,
+    mut defeated: ResMut<DefeatedEnemies>,
+    mut commands: Commands,
+) {
+    for event in level_changed_event.iter() {
+
+        //Only enemies outside the room being entered, so nothing appears in front of the player
+        let to_respawn: Vec<EntityIid> = defeated.respawning
+            .iter()
+            .filter(|(iid, enemy)| enemy.respawn == EnemyRespawn::OnRoomEntry && iid.level_uid != event.0)
+            .map(|(iid, _)| iid.clone())
+            .collect();
+
+        for iid in to_respawn {
+            if let Some(enemy) = defeated.respawning.remove(&iid) {
+                respawn_enemy(iid, enemy, &parent_query, &mut commands);
+            }
+        }
+    }
+}
+
+//Resting only happens when a new checkpoint is activated
+pub fn respawn_enemies_on_rest(
+    parent_query: Query<(), With<GlobalTransform>>,
+    mut checkpoint_event: EventReader<CheckpointActivatedEvent>,
+    mut retry_event: EventReader<RetryEvent>,
+    mut defeated: ResMut<DefeatedEnemies>,
+    mut commands: Commands,
+) {
+    //Retrying respawns all the levels, which brings the enemies back by itself
+    if retry_event.iter().count() > 0 {
+        defeated.respawning.clear();
+    }
+
+    if checkpoint_event.iter().count() == 0 {
+        return;
+    }
+
+    for (iid, enemy) in defeated.respawning.drain().collect::<Vec<_>>() {
+        respawn_enemy(iid, enemy, &parent_query, &mut commands);
+    }
+}
+
+//===============================================================
+
+//Walls block the view of enemies
+fn has_line_of_sight(physics_world: &PhysicsWorld, from: Vec2, to: Vec2) -> bool {
+    let layers = CollisionLayers::none()
+        .with_group(CollisionLayer::Entity)
+        .with_mask(CollisionLayer::Tile);
+
+    physics_world.ray_cast_with_filter(from.extend(0.), (to - from).extend(0.), true, layers, |_| true).is_none()
+}
+
+fn in_vision_cone(vision_cone: Option<f32>, facing: f32, to_target: Vec2) -> bool {
+    match vision_cone {
+        Some(cone) => to_target.angle_between(Vec2::new(facing, 0.)).abs() <= cone / 2.,
+        None => true,
+    }
+}
+
 pub fn enemy_target_player(
-    mut enemy_query: Query<(&NonPlayer, &mut NonPlayerAggressiveState, &NonPlayerAttackPlayer, &GlobalTransform), Without<Player>>,
+    mut enemy_query: Query<(&NonPlayer, &mut NonPlayerAggressiveState, &NonPlayerAttackPlayer, &GlobalTransform, Option<&MoveDir>, Option<&NonPlayerWander>), Without<Player>>,
     player_query: Query<(Entity, &GlobalTransform), With<Player>>,
-    //mut current_level: ResMut<LevelSelection>,
+    current_level: Res<LevelSelection>,
+    physics_world: PhysicsWorld,
 ) {
     if let Ok((player, player_transform)) = player_query.get_single() {
+
+        let player_position = player_transform.translation.truncate();
         
-        for (non_player, mut state, attack, transform) in enemy_query.iter_mut() {
-            //Check if enemy is in the same room as player //Todo
-            //if current_level.is_match(&0, non_player.0) {   
+        for (non_player, mut state, attack, transform, move_dir, wander) in enemy_query.iter_mut() {
 
-            //}
-            let dist_to_player = player_transform.translation.distance(transform.translation);
+            let position = transform.translation.truncate();
+            let dist_to_player = player_position.distance(position);
             if dist_to_player == 0. {
                 continue;
             }
-            //println!("Distance to player = {}", dist_to_player);
 
-            if dist_to_player < attack.attack_range {
-                *state = NonPlayerAggressiveState::Attack{target: player};
+            //Enemies in neighbouring rooms shouldn't notice the player
+            let same_level = *current_level == LevelSelection::Uid(non_player.0);
+            let can_see = same_level && has_line_of_sight(&physics_world, position, player_position);
+
+            let attacking = matches!(*state, NonPlayerAggressiveState::Attack { .. });
+
+            if attacking {
+                //Go looking where the player was last seen before giving up
+                if !can_see || dist_to_player > attack.lost_range {
+                    *state = NonPlayerAggressiveState::Search {
+                        last_seen: player_position,
+                        time_left: ENEMY_SEARCH_TIME,
+                    };
+                }
+                continue;
             }
-            else if dist_to_player > attack.lost_range && *state != NonPlayerAggressiveState::Wander {
-                *state = NonPlayerAggressiveState::Wander;
+
+            let facing = match (move_dir, wander) {
+                (Some(move_dir), _) if move_dir.0 != 0. => move_dir.0.signum(),
+                (_, Some(wander)) => wander.direction,
+                _ => 1.,
+            };
+
+            if can_see && dist_to_player < attack.attack_range && in_vision_cone(attack.vision_cone, facing, player_position - position) {
+                *state = NonPlayerAggressiveState::Attack{target: player};
             }
         }
     }
 }
 
 pub fn enemy_attack_target(
-    mut enemy_query: Query<(&NonPlayerAggressiveState, &GlobalTransform, Option<&mut MoveDir>, Option<&mut FullMoveDir>), Without<Player>>,
+    mut enemy_query: Query<(&NonPlayerAggressiveState, &mut NavPath), Without<Player>>,
     target_query: Query<&GlobalTransform, Without<NonPlayerAggressiveState>>,
 ) { 
 
-    for (state, transform, move_dir, full_move_dir) in enemy_query.iter_mut() {
+    for (state, mut nav_path) in enemy_query.iter_mut() {
 
-        match *state {
-            NonPlayerAggressiveState::Attack { target } => {
-                
-                if let Ok(target_transform) = target_query.get(target) {
+        if let NonPlayerAggressiveState::Attack { target } = *state {
+            if let Ok(target_transform) = target_query.get(target) {
+                nav_path.goal = Some(target_transform.translation.truncate());
+            }
+        }
+    }
+}
+
+//Aims each weapon at the target, then charges and releases it whenever the target is in range
+pub fn enemy_use_weapons(
+    enemy_query: Query<(&NonPlayerAggressiveState, &WeaponInventory, &GlobalTransform)>,
+    target_query: Query<&GlobalTransform, Without<NonPlayerAggressiveState>>,
+    mut weapon_query: Query<(&mut NonPlayerWeapon, &mut WeaponState, &mut WeaponDirection, &mut WeaponCharge)>,
+    time: Res<FrameDelta>,
+) {
 
-                    let new_move_direction = (target_transform.translation - transform.translation).truncate();
+    for (state, weapons, transform) in enemy_query.iter() {
 
-                    //new_move_direction = new_move_direction.normalize();
+        let target = match *state {
+            NonPlayerAggressiveState::Attack { target } => target_query.get(target).ok(),
+            _ => None,
+        };
 
-                    if let Some(mut move_dir) = move_dir {
-                        if new_move_direction.x > 0. {
-                            move_dir.0 = 1.
-                        } else if new_move_direction.x < 0. {
-                            move_dir.0 = -1.
-                        } else {
-                            move_dir.0 = 0.;
-                        }
-                    }
-                    if let Some(mut full_move_dir) = full_move_dir {
+        for slot in [weapons.get_slot1(), weapons.get_slot2()] {
+            let weapon_entity = match *slot {
+                Some(weapon_entity) => weapon_entity,
+                None => continue,
+            };
+            if let Ok((mut weapon, mut weapon_state, mut direction, mut charge)) = weapon_query.get_mut(weapon_entity) {
 
-                        if new_move_direction != Vec2::ZERO {
-                            full_move_dir.0 = new_move_direction.normalize();
-                        }
-                        else {
-                            full_move_dir.0 = Vec2::ZERO;
+                weapon.cooldown.tick(time.delta());
+
+                let target_transform = match target {
+                    Some(target_transform) => target_transform,
+                    None => {
+                        //Lost the target mid swing, let go without attacking
+                        if weapon_state.charging {
+                            charge.reset();
+                            weapon_state.charging = false;
+                            weapon.cooldown.reset();
                         }
+                        continue;
+                    },
+                };
+
+                let to_target = (target_transform.translation - transform.translation).truncate();
 
+                if      to_target.x > 0. { direction.right_facing = true; }
+                else if to_target.x < 0. { direction.right_facing = false; }
+                direction.direction = WeaponDirections::from_vec2(to_target.normalize_or_zero().round());
+
+                if weapon_state.charging {
+                    weapon.charge.tick(time.delta());
+                    if weapon.charge.finished() {
+                        weapon_state.charging = false;
+                        weapon.cooldown.reset();
                     }
                 }
-            },
-            _ => {}
+                else if weapon.cooldown.finished() && to_target.length() <= weapon.range {
+                    weapon_state.charging = true;
+                    weapon.charge.reset();
+                }
+            }
         }
     }
 }
 
-pub fn enemy_idle (
-    mut enemy_query: Query<(&NonPlayerAggressiveState, Option<&mut MoveDir>, Option<&mut FullMoveDir>)>,
+//Casts a ray down just in front of a ground enemy to see if it would walk off a ledge
+fn ground_ahead(physics_world: &PhysicsWorld, position: Vec2, half_size: Vec2, direction: f32) -> bool {
+    let start = Vec3::new(position.x + (half_size.x + 1.) * direction, position.y, 0.);
+    let ray = Vec3::new(0., -(half_size.y + ENEMY_LEDGE_CHECK_DEPTH), 0.);
+    let layers = CollisionLayers::none()
+        .with_group(CollisionLayer::Entity)
+        .with_mask(CollisionLayer::Tile);
+
+    physics_world.ray_cast_with_filter(start, ray, true, layers, |_| true).is_some()
+}
+
+pub fn enemy_wander (
+    mut enemy_query: Query<(
+        &mut NonPlayerWander, &GlobalTransform, &CollisionShape,
+        Option<&NonPlayerAggressiveState>, Option<&NonPlayerPassiveState>,
+        Option<&mut MoveDir>, Option<&mut FullMoveDir>, Option<&IsGrounded>, Option<&IsOnWall>,
+    )>,
+    physics_world: PhysicsWorld,
+    time: Res<FrameDelta>,
 ) {
-    for (state, move_dir, full_move_dir) in enemy_query.iter_mut() {
+    for (mut wander, transform, shape, aggressive, passive, move_dir, full_move_dir, grounded, on_wall) in enemy_query.iter_mut() {
 
-        match *state {
-            NonPlayerAggressiveState::Wander => {
+        let wandering = matches!(aggressive, Some(NonPlayerAggressiveState::Wander))
+            || matches!(passive, Some(NonPlayerPassiveState::Wander));
+        if !wandering {
+            continue;
+        }
 
-                if let Some(mut move_dir) = move_dir {
+        let position = transform.translation.truncate();
 
-                    move_dir.0 = 0.;
+        //Ground enemies patrol, turning around at walls and ledges
+        if let Some(mut move_dir) = move_dir {
+            wander.turn_cooldown.tick(time.delta());
 
+            if !grounded.is_some_and(|grounded| grounded.grounded) {
+                move_dir.0 = 0.;
+                continue;
+            }
+
+            if wander.turn_cooldown.finished() {
+                let half_size = match shape {
+                    CollisionShape::Cuboid { half_extends, .. } => half_extends.truncate(),
+                    _ => Vec2::ZERO,
+                };
+                let hit_wall = on_wall.is_some_and(|on_wall| on_wall.on_wall);
+
+                if hit_wall || !ground_ahead(&physics_world, position, half_size, wander.direction) {
+                    wander.direction = -wander.direction;
+                    wander.turn_cooldown.reset();
                 }
-                if let Some(mut full_move_dir) = full_move_dir {
-                    full_move_dir.0 = Vec2::ZERO;
-                }
+            }
+            move_dir.0 = wander.direction;
+        }
+
+        //Flying enemies drift between points around where they started, resting at each one
+        if let Some(mut full_move_dir) = full_move_dir {
+            if wander.home.is_none() {
+                wander.home = Some(position);
+            }
+
+            let target = match wander.drift_target {
+                Some(target) => target,
+                None => match wander.next_drift_target() {
+                    Some(target) => target,
+                    None => continue,
+                },
+            };
+
+            if position.distance(target) > ENEMY_DRIFT_REACHED {
+                full_move_dir.0 = (target - position).normalize();
+                continue;
+            }
+
+            full_move_dir.0 = Vec2::ZERO;
+            wander.drift_timer.tick(time.delta());
+            if wander.drift_timer.finished() {
+                wander.drift_timer.reset();
+                wander.next_drift_target();
+            }
+        }
+    }
+}
+
+//Heads to where the player was last seen, then goes back to wandering
+pub fn enemy_search (
+    mut enemy_query: Query<(&mut NonPlayerAggressiveState, &GlobalTransform, &mut NavPath)>,
+    time: Res<FrameDelta>,
+) {
+    for (mut state, transform, mut nav_path) in enemy_query.iter_mut() {
+
+        if let NonPlayerAggressiveState::Search { last_seen, time_left } = *state {
+            let position = transform.translation.truncate();
+            let time_left = time_left - time.delta_seconds();
 
+            if time_left <= 0. || position.distance(last_seen) < ENEMY_SEARCH_REACHED {
+                *state = NonPlayerAggressiveState::Wander;
+                continue;
+            }
+
+            *state = NonPlayerAggressiveState::Search { last_seen, time_left };
+            nav_path.goal = Some(last_seen);
+        }
+    }
+}
+
+pub fn enemy_flee_player(
+    mut enemy_query: Query<(&mut NonPlayerPassiveState, &NonPlayerFleePlayer, &GlobalTransform), Without<Player>>,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+) {
+    if let Ok((player, player_transform)) = player_query.get_single() {
+
+        for (mut state, flee, transform) in enemy_query.iter_mut() {
+            let dist_to_player = player_transform.translation.distance(transform.translation);
+
+            match *state {
+                NonPlayerPassiveState::Wander if dist_to_player < flee.flee_range => {
+                    *state = NonPlayerPassiveState::Flee { target: player };
+                },
+                NonPlayerPassiveState::Flee { .. } if dist_to_player > flee.safe_range => {
+                    *state = NonPlayerPassiveState::Wander;
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn enemy_flee_target(
+    mut enemy_query: Query<(&NonPlayerPassiveState, &GlobalTransform, Option<&mut MoveDir>, Option<&mut FullMoveDir>), Without<Player>>,
+    target_query: Query<&GlobalTransform, Without<NonPlayerPassiveState>>,
+) {
+    for (state, transform, move_dir, full_move_dir) in enemy_query.iter_mut() {
+
+        if let NonPlayerPassiveState::Flee { target } = *state {
+            if let Ok(target_transform) = target_query.get(target) {
+
+                //Move towards the point mirrored around the enemy, directly away from the target
+                let position = transform.translation.truncate();
+                let away = position * 2. - target_transform.translation.truncate();
+                steer_towards(position, away, move_dir, full_move_dir);
             }
-            _ => {}
         }
     }
 }
@@ -105,47 +477,40 @@ pub fn enemy_idle (
 
 pub fn enemy_damage (
     mut collision_events: EventReader<CollisionEvent>,
-    enemy_query: Query<&NonPlayerDamage>,
+    enemy_query: Query<(&NonPlayerDamage, &GlobalTransform)>,
     mut damage_event: EventWriter<HealthChangeEvent>,
 ) {
 
     for event in collision_events.iter() {
-        match event {
-            CollisionEvent::Started(d1, d2) => {
-            
-                let d1_player = if d1.collision_layers().contains_group(CollisionLayer::Player) {
-                    true
-                } else {
-                    false
-                };
-                let d2_player = if d2.collision_layers().contains_group(CollisionLayer::Player) {
-                    true
-                } else {
-                    false
-                };
+        if let CollisionEvent::Started(d1, d2) = event {
+        
+            let d1_player = d1.collision_layers().contains_group(CollisionLayer::Player);
+            let d2_player = d2.collision_layers().contains_group(CollisionLayer::Player);
 
-                //Either both or neither collisions were player
-                if (d1_player && d2_player) || (!d1_player && !d2_player) { 
-                    continue;
-                }
+            //Either both or neither collisions were player
+            if (d1_player && d2_player) || (!d1_player && !d2_player) { 
+                continue;
+            }
 
-                let (player, to_check) = if d1_player {
-                    (d1, d2)
-                } else {
-                    (d2, d1)
-                };
+            let (player, to_check) = if d1_player {
+                (d1, d2)
+            } else {
+                (d2, d1)
+            };
 
 
-                if to_check.collision_layers().contains_group(CollisionLayer::Enemy) {
-                    if let Ok(damage) = enemy_query.get(to_check.rigid_body_entity()) {
-                        damage_event.send(HealthChangeEvent {
-                            entity: player.collision_shape_entity(),
-                            change_type: HealthChangeType::Add{value: -damage.0},
-                        })
-                    }
+            if to_check.collision_layers().contains_group(CollisionLayer::Enemy) {
+                if let Ok((damage, transform)) = enemy_query.get(to_check.rigid_body_entity()) {
+                    damage_event.send(HealthChangeEvent {
+                        entity: player.collision_shape_entity(),
+                        change_type: HealthChangeType::Damage(damage.0.clone()),
+                        knockback: Some(Knockback {
+                            source: transform.translation.truncate(),
+                            force: ENEMY_CONTACT_KNOCKBACK,
+                        }),
+                    })
                 }
             }
-            _ => {}
         }
     }
 }
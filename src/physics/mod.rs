@@ -2,6 +2,8 @@
 
 use bevy::prelude::*;
 
+use crate::general::GameState;
+
 pub mod physics_components;
 mod physics_systems;
 
@@ -13,17 +15,21 @@ impl Plugin for CustomPhysicsPlugin {
         app
             .add_event::<physics_components::GroundedEvent>()
 
-            .add_system(physics_systems::cap_velocity)
-            .add_system(physics_systems::apply_movespeed)
-            .add_system(physics_systems::apply_full_movespeed)
-
-            .add_system(physics_systems::apply_jump)
-            .add_system(physics_systems::reset_jump.after("GroundCheck"))
-
-            .add_system(physics_systems::check_on_ground.label("GroundCheck"))
-            .add_system(physics_systems::check_on_wall)
-
-            .add_system(physics_systems::set_gravity_scale)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(physics_systems::check_on_ground.label("GroundCheck"))
+                    .with_system(physics_systems::check_on_wall)
+                    .with_system(physics_systems::reset_jump.label("ResetJump").after("GroundCheck"))
+
+                    //Runs after everything deciding where things move, in the same order every frame.
+                    //Otherwise the same input can take effect a frame sooner or later
+                    .with_system(physics_systems::apply_movespeed.label("ApplyMovement").after("MovementInput").after("ApplyDamage"))
+                    .with_system(physics_systems::apply_full_movespeed.label("ApplyMovement").after("MovementInput").after("ApplyDamage"))
+                    .with_system(physics_systems::apply_jump.label("ApplyMovement").after("MovementInput").after("ApplyDamage").after("ResetJump"))
+                    .with_system(physics_systems::cap_velocity.after("ApplyMovement"))
+
+                    .with_system(physics_systems::set_gravity_scale.after("MovementInput"))
+            )
         ;
     }
 }
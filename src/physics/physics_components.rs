@@ -3,6 +3,11 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::LdtkIntCell;
 use heron::prelude::*;
+use serde::Deserialize;
+
+//===============================================================
+
+pub const GRAVITY: f32 = -500.;
 
 //===============================================================
 
@@ -43,7 +48,6 @@ impl ColliderBundle {
                 CollisionLayer::Player, 
                 CollisionLayer::Tile)
                 .with_mask(CollisionLayer::Enemy),
-            ..Default::default()
         }
     }
 
@@ -67,6 +71,24 @@ impl ColliderBundle {
                 .with_mask(CollisionLayer::Tile)
                 .with_mask(CollisionLayer::Weapon)
                 .with_mask(CollisionLayer::Player),
+        }
+    }
+
+    //Static level geometry. Walls are put in the Tile group and collide with everything else
+    pub fn wall(width: f32, height: f32) -> Self {
+        Self {
+            collider: CollisionShape::Cuboid {
+                half_extends: Vec3::new(width, height, 0.) / 2.,
+                border_radius: None,
+            },
+            rigid_body: RigidBody::Static,
+            physic_material: PhysicMaterial {
+                friction: 0.,
+                ..Default::default()
+            },
+            collision_layer: CollisionLayers::all_masks::<CollisionLayer>()
+                .without_mask(CollisionLayer::Tile)
+                .with_group(CollisionLayer::Tile),
             ..Default::default()
         }
     }
@@ -96,7 +118,6 @@ impl ColliderBundle {
                 friction: 0.,
             },
             collision_layer,
-            ..Default::default()
         }
     }
 }
@@ -199,7 +220,7 @@ pub struct IsOnWall {
 
 //===============================================================
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Debug, Deserialize)]
 pub struct SetGravityScale {
     pub scale: f32,
     pub reset_velocity: bool,
@@ -212,13 +233,5 @@ impl Default for SetGravityScale {
         }
     }
 }
-impl SetGravityScale {
-    pub fn reset_velocity(scale: f32) -> SetGravityScale {
-        SetGravityScale {
-            scale,
-            reset_velocity: false,
-        }
-    }
-}
 
 //===============================================================
\ No newline at end of file
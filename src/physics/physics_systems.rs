@@ -10,7 +10,7 @@ use heron::{
         },
     };
 
-use crate::general::tools::{clamp_shift, lerp};
+use crate::general::{tools::{clamp_shift, lerp}, general_components::{HitStun, FrameDelta}};
 
 use super::physics_components::*;
 
@@ -18,7 +18,7 @@ use super::physics_components::*;
 
 pub fn cap_velocity(
     mut query: Query<(&mut Velocity, &MaxVelocity, Option<&Accel>, Option<&IsGrounded>)>,
-    time: Res<Time>,
+    time: Res<FrameDelta>,
 ) {
     let delta = time.delta().as_secs_f32();
 
@@ -60,10 +60,14 @@ pub fn cap_velocity(
 }
 
 pub fn apply_movespeed (
-    mut query: Query<(&MoveDir, &Accel, &mut Velocity)>,
-    time: Res<Time>,
+    mut query: Query<(&MoveDir, &Accel, &mut Velocity, Option<&HitStun>)>,
+    time: Res<FrameDelta>,
 ) {
-    for (move_dir, accel, mut velocity) in query.iter_mut() {
+    for (move_dir, accel, mut velocity, hit_stun) in query.iter_mut() {
+        if hit_stun.is_some_and(HitStun::is_stunned) {
+            continue;
+        }
+
         if move_dir.0 != 0. {
             velocity.linear.x += move_dir.0 * accel.accel * time.delta().as_secs_f32();
         }
@@ -79,11 +83,15 @@ pub fn apply_movespeed (
 }
 
 pub fn apply_full_movespeed (
-    mut query: Query<(&FullMoveDir, &Accel, &mut Velocity)>,
-    time: Res<Time>,
+    mut query: Query<(&FullMoveDir, &Accel, &mut Velocity, Option<&HitStun>)>,
+    time: Res<FrameDelta>,
 ) {
 
-    for (move_dir, accel, mut velocity) in query.iter_mut() {
+    for (move_dir, accel, mut velocity, hit_stun) in query.iter_mut() {
+        if hit_stun.is_some_and(HitStun::is_stunned) {
+            continue;
+        }
+
         if move_dir.0 != Vec2::ZERO {
             velocity.linear += (move_dir.0 * accel.accel * time.delta().as_secs_f32()).extend(0.);
         }
@@ -110,11 +118,14 @@ pub fn apply_full_movespeed (
 //=================================================================================
 
 pub fn apply_jump (
-    mut query: Query<(&mut CanJump, &mut Velocity, &IsGrounded)>,
-    time: Res<Time>
+    mut query: Query<(&mut CanJump, &mut Velocity, &IsGrounded, Option<&HitStun>)>,
+    time: Res<FrameDelta>
 ) {
 
-    for (mut can_jump, mut velocity, grounded) in query.iter_mut() {
+    for (mut can_jump, mut velocity, grounded, hit_stun) in query.iter_mut() {
+        if hit_stun.is_some_and(HitStun::is_stunned) {
+            continue;
+        }
         
         //Already jumping and jump is still pressed and still time left on jump
         if can_jump.jumping && can_jump.jump_pressed && !can_jump.jump_timer.finished() {
@@ -168,7 +179,7 @@ pub fn check_on_ground (
     mut collision_event: EventReader<CollisionEvent>,
     mut grounded_query: Query<&mut IsGrounded>,
     mut grounded_event: EventWriter<GroundedEvent>,
-    time: Res<Time>,
+    time: Res<FrameDelta>,
 ) {
     let down_dir = Vec2::new(0.,-1.);
 
@@ -176,13 +187,9 @@ pub fn check_on_ground (
         match event {
             CollisionEvent::Started(d1, d2) => {
 
-                let d1_tile = if d1.collision_layers().contains_group(CollisionLayer::Tile) { 
-                            true
-                } else {    false };
+                let d1_tile = d1.collision_layers().contains_group(CollisionLayer::Tile);
 
-                let d2_tile = if d2.collision_layers().contains_group(CollisionLayer::Tile) {
-                            true
-                } else {    false };
+                let d2_tile = d2.collision_layers().contains_group(CollisionLayer::Tile);
 
                 //Either both or neither collisions were player
                 if (d1_tile && d2_tile) || (!d1_tile && !d2_tile) { 
@@ -197,7 +204,7 @@ pub fn check_on_ground (
                 //else {      println!("d2 is the tile. CheckNormals = {:?}, TileNormals = {:?}", to_check.normals(), tile.normals()); }
 
 
-                if tile.normals().len() == 0 {
+                if tile.normals().is_empty() {
                     //println!("There are no normals");
                     continue;
                 }
@@ -216,16 +223,8 @@ pub fn check_on_ground (
             }
             CollisionEvent::Stopped(d1, d2) => {
 
-                let d1_tile = if d1.collision_layers().contains_group(CollisionLayer::Tile) {
-                    true
-                } else {
-                    false
-                };
-                let d2_tile = if d2.collision_layers().contains_group(CollisionLayer::Tile) {
-                    true
-                } else {
-                    false
-                };
+                let d1_tile = d1.collision_layers().contains_group(CollisionLayer::Tile);
+                let d2_tile = d2.collision_layers().contains_group(CollisionLayer::Tile);
 
                 //Either both or neither collisions were player
                 if (d1_tile && d2_tile) || (!d1_tile && !d2_tile) { 
@@ -243,7 +242,7 @@ pub fn check_on_ground (
                     let to_remove = tile.rigid_body_entity();
                     grounded.walls_below.retain(|&x| x != to_remove);
 
-                    if grounded.walls_below.len() == 0 {
+                    if grounded.walls_below.is_empty() {
                         grounded.grounded = false;
                     }
                 }
@@ -273,13 +272,9 @@ pub fn check_on_wall (
         match event {
             CollisionEvent::Started(d1, d2) => {
 
-                let d1_tile = if d1.collision_layers().contains_group(CollisionLayer::Tile) { 
-                            true
-                } else {    false };
+                let d1_tile = d1.collision_layers().contains_group(CollisionLayer::Tile);
 
-                let d2_tile = if d2.collision_layers().contains_group(CollisionLayer::Tile) {
-                            true
-                } else {    false };
+                let d2_tile = d2.collision_layers().contains_group(CollisionLayer::Tile);
 
                 //Either both or neither collisions were player
                 if (d1_tile && d2_tile) || (!d1_tile && !d2_tile) { 
@@ -292,7 +287,7 @@ pub fn check_on_wall (
 
 
 
-                if to_check.normals().len() == 0 {
+                if to_check.normals().is_empty() {
                     continue;
                 }
 
@@ -306,13 +301,9 @@ pub fn check_on_wall (
             }
             CollisionEvent::Stopped(d1, d2) => {
 
-                let d1_tile = if d1.collision_layers().contains_group(CollisionLayer::Tile) { 
-                            true
-                } else {    false };
+                let d1_tile = d1.collision_layers().contains_group(CollisionLayer::Tile);
 
-                let d2_tile = if d2.collision_layers().contains_group(CollisionLayer::Tile) {
-                            true
-                } else {    false };
+                let d2_tile = d2.collision_layers().contains_group(CollisionLayer::Tile);
 
                 //Either both or neither collisions were player
                 if (d1_tile && d2_tile) || (!d1_tile && !d2_tile) { 
@@ -330,7 +321,7 @@ pub fn check_on_wall (
                     let to_remove = tile.rigid_body_entity();
                     on_wall.walls_touching.retain(|&x| x != to_remove);
 
-                    if on_wall.walls_touching.len() == 0 {
+                    if on_wall.walls_touching.is_empty() {
                         on_wall.on_wall = false;
                         //println!("Something has left the wall");
                     }
@@ -3,6 +3,8 @@
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::RegisterLdtkObjects;
 
+use crate::general::GameState;
+
 pub mod player_components;
 mod player_systems;
 
@@ -14,17 +16,30 @@ impl Plugin for PlayerPlugin {
         app
             .register_ldtk_entity::<player_components::PlayerBundle>("Player")
 
-            .add_system(player_systems::player_move.label("PlayerMoveInput"))
-            .add_system(player_systems::player_sprint)
+            .insert_resource(player_components::PlayerDeathTimer(
+                Timer::from_seconds(player_components::PLAYER_DEATH_TIME, false)
+            ))
+
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_systems::player_move.label("PlayerMoveInput").label("MovementInput"))
+                    .with_system(player_systems::player_sprint.label("PlayerSprint").label("MovementInput"))
+
+                    .with_system(player_systems::player_jump.label("MovementInput"))
+
+                    .with_system(player_systems::player_wall_cling.label("MovementInput").before("PlayerMoveInput"))
+                    .with_system(player_systems::player_cling_cooldown)
+                    .with_system(player_systems::player_wall_fling.label("MovementInput"))
 
-            .add_system(player_systems::player_jump)
+                    .with_system(player_systems::player_attack)
+                    .with_system(player_systems::player_weapon_aim)
 
-            .add_system(player_systems::player_wall_cling.before("PlayerMoveInput"))
-            .add_system(player_systems::player_cling_cooldown)
-            .add_system(player_systems::player_wall_fling)
-            
-            .add_system(player_systems::player_attack)
-            .add_system(player_systems::player_weapon_aim)
+                    .with_system(player_systems::player_death)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Dead)
+                    .with_system(player_systems::tick_death_timer)
+            )
 
             //Debug systems
             //.add_system(player_systems::_equip_player_weapon)
@@ -13,7 +13,7 @@ use crate::{
     weapons::weapon_components::WeaponInventoryBundle,
     general::{
         tools::load_texture_atlas,
-        general_components::Health,
+        general_components::{Health, HitStun},
     },
 };
 
@@ -24,21 +24,8 @@ pub const PLAYER_HEIGHT:            f32 = 32.;
 
 //===============================================================
 
-pub const PLAYER_LEFT:              KeyCode = KeyCode::Left;
-pub const PLAYER_RIGHT:             KeyCode = KeyCode::Right;
-
-pub const PLAYER_JUMP:              KeyCode = KeyCode::Space;
-pub const PLAYER_CLING:             KeyCode = KeyCode::X;
-pub const PLAYER_SPRINT:            KeyCode = KeyCode::Z;
-
-pub const PLAYER_PRIMARY_ATTACK:    KeyCode = KeyCode::C;
-pub const PLAYER_SECONDARY_ATTACK:  KeyCode = KeyCode::A;
-
-pub const PLAYER_INTERACT:          KeyCode = KeyCode::Z;
-
-//===============================================================
-
 pub const PLAYER_MAX_HEALTH:        i32 = 200;
+pub const PLAYER_DEATH_TIME:        f32 = 1.5;
 
 pub const PLAYER_MAX_SPEED:         f32 = 120.;
 pub const PLAYER_MAX_FALL_SPEED:    f32 = 400.;
@@ -94,6 +81,9 @@ impl PlayerWallCling {
 #[derive(Component, Default, Clone)]
 pub struct Player;
 
+//Runs while the death animation plays, the game over menu shows once it finishes
+pub struct PlayerDeathTimer(pub Timer);
+
 #[derive(Bundle, Clone, Default)]
 pub struct PlayerWallBundle {
     player_cling: PlayerWallCling,
@@ -105,6 +95,7 @@ pub struct PlayerWallBundle {
 pub struct PlayerBundle {
     player:         Player,
     health:         Health,
+    hit_stun:       HitStun,
     pub worldly:    Worldly,
     #[bundle]
     sprite:         SpriteSheetBundle,
@@ -122,13 +113,9 @@ pub struct PlayerBundle {
     wall_bundle:    PlayerWallBundle,
 }
 
-//Spawn the player
-impl LdtkEntity for PlayerBundle {
-    fn bundle_entity(
-        entity_instance: &EntityInstance,
-        layer_instance: &LayerInstance,
-        _: Option<&Handle<Image>>,
-        _: Option<&TilesetDefinition>,
+impl PlayerBundle {
+    pub fn new(
+        transform: Transform,
         assets: &AssetServer,
         texture_atlases: &mut Assets<TextureAtlas>,
     ) -> Self {
@@ -160,6 +147,12 @@ impl LdtkEntity for PlayerBundle {
             Vec2::new(32., 32.), 1, 1,
         );
 
+        //Death Animation
+        let death_atlas_handle = load_texture_atlas(assets, texture_atlases,
+            "Textures/Alchemist/Mini_Alchemist_Death.png",
+            Vec2::new(32., 32.), 4, 1,
+        );
+
         //Wall Hang Animation
         let wall_grab_atlas_handle = load_texture_atlas(assets, texture_atlases,
             "Textures/Alchemist/Mini_Alchemist_Wallgrab.png",
@@ -241,13 +234,24 @@ impl LdtkEntity for PlayerBundle {
             )
         );
 
+        //Collapses quickly, then holds the last frame while the player fades out
+        sprite_sheet_animation.add_animation(
+            AnimationType::Custom("Death".to_string()),
+            Animation::with_custom_framesteps(
+                death_atlas_handle,
+                vec!(0.15, 0.15, 0.2, PLAYER_DEATH_TIME - 0.5),
+                4,
+                false,
+            )
+        );
+
         Self {
             player: Player,
             health: Health::new(PLAYER_MAX_HEALTH, 1.),
-            worldly: Worldly::from_entity_info(entity_instance, layer_instance),
+            hit_stun: HitStun::default(),
             sprite: SpriteSheetBundle {
                 texture_atlas: idle_atlas_handle,
-                transform: Transform::from_xyz(0., entity_instance.height as f32 * 2., 2.),
+                transform,
                 ..Default::default()
             },
             animation: sprite_sheet_animation,
@@ -279,4 +283,23 @@ impl LdtkEntity for PlayerBundle {
     }
 }
 
+//Spawn the player
+impl LdtkEntity for PlayerBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        assets: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+
+        let transform = Transform::from_xyz(0., entity_instance.height as f32 * 2., 2.);
+
+        let mut player = PlayerBundle::new(transform, assets, texture_atlases);
+        player.worldly = Worldly::from_entity_info(entity_instance, layer_instance);
+        player
+    }
+}
+
 //===============================================================
\ No newline at end of file
@@ -13,26 +13,28 @@ use crate::{
          MaxVelocity, 
         MoveDir, CanJump, IsGrounded, IsOnWall, SetGravityScale
     }, 
-    weapons::weapon_components::{
-        WeaponState, WeaponBundle, WeaponInventory,
-        WeaponDirection, WeaponDirections
-    }, general::general_components::{HealthChangeEvent, HealthChangeType},
+    weapons::{
+        weapon_components::{
+            WeaponState, WeaponInventory,
+            WeaponDirection, WeaponDirections
+        },
+        weapon_prefabs::SWORD_ID,
+    }, general::{
+        GameState,
+        general_components::{HealthChangeEvent, HealthChangeType, EntityDiedEvent, FadeInOut, FrameDelta, StatusEffects},
+    },
+    controls::controls_components::{ActionInput, PlayerAction},
+    world::{ItemPickedUpEvent, PlayerPickupType},
 };
 
 //===============================================================
 
 pub fn player_move(
     mut query: Query<(&mut MoveDir, &PlayerWallCling), With<Player>>,
-    key_input: Res<Input<KeyCode>>,
+    action_input: Res<ActionInput>,
 ) {
 
-    let mut x_dir = 0.;
-    if key_input.pressed(PLAYER_LEFT) {
-        x_dir -= 1.;
-    }
-    if key_input.pressed(PLAYER_RIGHT) {
-        x_dir += 1.;
-    }
+    let x_dir = action_input.move_dir().x;
 
     for (mut move_dir, cling) in query.iter_mut() {
 
@@ -43,28 +45,34 @@ pub fn player_move(
 }
 
 pub fn player_sprint(
-    mut player_query: Query<(&mut MaxVelocity, &PlayerSprint, &IsGrounded)>,
-    key_input: Res<Input<KeyCode>>,
+    mut player_query: Query<(&mut MaxVelocity, &PlayerSprint, &IsGrounded, Option<&mut StatusEffects>)>,
+    action_input: Res<ActionInput>,
 ) {
-    let sprinting = key_input.pressed(PLAYER_SPRINT);
+    let sprinting = action_input.pressed(PlayerAction::Sprint);
 
-    for (mut max_vel, sprint, grounded) in player_query.iter_mut() {
+    for (mut max_vel, sprint, grounded, mut status_effects) in player_query.iter_mut() {
 
-        if sprinting && grounded.grounded && sprint.can_sprint {
-            max_vel.x = sprint.sprint_speed;
+        let speed = if sprinting && grounded.grounded && sprint.can_sprint {
+            sprint.sprint_speed
         }
         else {
-            max_vel.x = sprint.normal_speed;
+            sprint.normal_speed
+        };
+
+        //While slowed, change the speed the slow is applied to instead
+        match status_effects.as_mut().and_then(|status_effects| status_effects.base_max_velocity.as_mut()) {
+            Some(base_max_velocity) => base_max_velocity.x = speed,
+            None => max_vel.x = speed,
         }
     }
 }
 
 pub fn player_jump(
     mut query: Query<&mut CanJump, With<Player>>,
-    key_input: Res<Input<KeyCode>>,
+    action_input: Res<ActionInput>,
 ) {
-    let jump_pressed = key_input.pressed(PLAYER_JUMP);
-    let jump_just_pressed = key_input.just_pressed(PLAYER_JUMP);
+    let jump_pressed = action_input.pressed(PlayerAction::Jump);
+    let jump_just_pressed = action_input.just_pressed(PlayerAction::Jump);
 
     for mut can_jump in query.iter_mut() {
 
@@ -79,7 +87,7 @@ pub fn player_jump(
 
 pub fn player_cling_cooldown(
     mut cling_query: Query<&mut PlayerWallCling>,
-    time: Res<Time>,
+    time: Res<FrameDelta>,
 ) {
     for mut cling in cling_query.iter_mut() {
         cling.cling_cooldown.tick(time.delta());
@@ -89,10 +97,10 @@ pub fn player_cling_cooldown(
 pub fn player_wall_cling(
     mut player_query: Query<(Entity, &IsOnWall, &IsGrounded, &mut PlayerWallCling, Option<&mut Velocity>, Option<&mut AutoAnimation>), With<Player> >,
     mut animation_event: EventWriter<ChangeAnimationEvent>,
-    key_input: Res<Input<KeyCode>>,
+    action_input: Res<ActionInput>,
     mut commands: Commands,
 ) {
-    let climb_pressed = key_input.pressed(PLAYER_CLING);
+    let climb_pressed = action_input.pressed(PlayerAction::Cling);
 
     for (entity, on_wall, grounded, mut player_cling, velocity, auto_animation) in player_query.iter_mut() {
 
@@ -150,10 +158,9 @@ pub fn player_wall_cling(
                     //Get player input and set animation.
                     else {
 
-                        let mut dir = Vec2::ZERO;
-                        if key_input.pressed(KeyCode::Right)    { dir.x += 1.; }
-                        if key_input.pressed(KeyCode::Left)     { dir.x -= 1.; }
-                        if key_input.pressed(KeyCode::Up)       { dir.y += 1.; }
+                        let mut dir = action_input.move_dir();
+                        //Can't fling downwards
+                        dir.y = dir.y.max(0.);
 
                         if dir != Vec2::ZERO {
                             dir = dir.normalize();
@@ -243,15 +250,10 @@ pub fn player_wall_fling(
 pub fn player_weapon_aim(
     player_query: Query<&WeaponInventory, With<Player>>,
     mut weapon_query: Query<&mut WeaponDirection>,
-    key_input: Res<Input<KeyCode>>,
+    action_input: Res<ActionInput>,
 ) { 
 
-    let mut dir = Vec2::ZERO;
-    if key_input.pressed(KeyCode::Right)    { dir.x += 1.; }
-    if key_input.pressed(KeyCode::Left)     { dir.x -= 1.; }
-
-    if key_input.pressed(KeyCode::Up)       { dir.y += 1.; }
-    if key_input.pressed(KeyCode::Down)     { dir.y -= 1.; }
+    let dir = action_input.aim_dir();
 
     if dir == Vec2::ZERO { return }
 
@@ -281,7 +283,7 @@ pub fn player_weapon_aim(
 pub fn player_attack(
     player_query: Query<&WeaponInventory, With<Player>>,
     mut weapon_query: Query<&mut WeaponState>,
-    key_input: Res<Input<KeyCode>>,
+    action_input: Res<ActionInput>,
 ) {
 
     if let Ok(weapons) = player_query.get_single() {
@@ -301,60 +303,81 @@ pub fn player_attack(
             }
         };
 
-        let primary_pressed = key_input.just_pressed(PLAYER_PRIMARY_ATTACK);
-        let primary_released = key_input.just_released(PLAYER_PRIMARY_ATTACK);
+        let primary_pressed = action_input.just_pressed(PlayerAction::PrimaryAttack);
+        let primary_released = action_input.just_released(PlayerAction::PrimaryAttack);
         let primary_weapon = weapons.get_slot1();
         set_weapon_charge(primary_pressed, primary_released, primary_weapon);
 
 
-        let secondary_pressed   = key_input.just_pressed(PLAYER_SECONDARY_ATTACK);
-        let secondary_released  = key_input.just_released(PLAYER_SECONDARY_ATTACK);
+        let secondary_pressed   = action_input.just_pressed(PlayerAction::SecondaryAttack);
+        let secondary_released  = action_input.just_released(PlayerAction::SecondaryAttack);
         let secondary_weapon = weapons.get_slot2();
         set_weapon_charge(secondary_pressed, secondary_released, secondary_weapon);
 
     }
 }
 
+//===============================================================
 
-pub fn _equip_player_weapon(
-    mut player_query: Query<(&mut WeaponInventory, Entity), With<Player>>,
-    key_input: Res<Input<KeyCode>>,
+pub fn player_death(
+    mut player_query: Query<(&mut AutoAnimation, &WeaponInventory), With<Player>>,
+    mut weapon_query: Query<&mut WeaponState>,
+    mut died_event: EventReader<EntityDiedEvent>,
+    mut change_animation_event: EventWriter<ChangeAnimationEvent>,
+    mut death_timer: ResMut<PlayerDeathTimer>,
+    mut game_state: ResMut<State<GameState>>,
     mut commands: Commands,
-    assets: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
-    
-    if key_input.just_pressed(KeyCode::P) {
-
-        let (mut inv, player) = player_query.single_mut();
-        let new_weapon = commands.spawn_bundle(WeaponBundle::create_throwing_knife(&assets, &mut texture_atlases, true)).id();
+    for event in died_event.iter() {
+        if let Ok((mut auto_anim, weapons)) = player_query.get_mut(event.0) {
+
+            auto_anim.disabled = true;
+            change_animation_event.send(ChangeAnimationEvent {
+                entity: event.0,
+                new_animation: AnimationType::Custom("Death".to_string()),
+                restart_animation: true,
+                flipped: FlipAnimation::None,
+            });
+            commands.entity(event.0).insert(FadeInOut {
+                timer: Timer::from_seconds(PLAYER_DEATH_TIME, false),
+                from: 1.,
+                to: 0.,
+                remove_on_finish: false,
+                remove_component_on_finish: true,
+            });
+
+            //Input is frozen while dead, so a held attack would never be released
+            for slot in [*weapons.get_slot1(), *weapons.get_slot2()] {
+                if let Some(mut weapon) = slot.and_then(|slot| weapon_query.get_mut(slot).ok()) {
+                    weapon.charging = false;
+                }
+            }
 
-        if inv.add_slot1_weapon(new_weapon) {
-            //Weapon added successfully
-            commands.entity(player).add_child(new_weapon);
-            println!("Weapon 1 spawned and equipped successfully");
-        }
-        else {
-            //Weapon was not added
-            commands.entity(new_weapon).despawn();  
-            println!("Weapon 1 was not added and is being despawned");
+            death_timer.0.reset();
+            let _ = game_state.set(GameState::Dead);
         }
     }
-    else if key_input.just_pressed(KeyCode::O) {
+}
 
-        let (mut inv, player) = player_query.single_mut();
-        let new_weapon = commands.spawn_bundle(WeaponBundle::create_sword(&assets, &mut texture_atlases, true)).id();
+pub fn tick_death_timer(
+    mut death_timer: ResMut<PlayerDeathTimer>,
+    time: Res<FrameDelta>,
+) {
+    death_timer.0.tick(time.delta());
+}
 
-        if inv.add_slot2_weapon(new_weapon) {
-            //Weapon added successfully
-            commands.entity(player).add_child(new_weapon);
-            println!("Weapon 2 spawned and equipped successfully");
-        }
-        else {
-            //Weapon was not added
-            commands.entity(new_weapon).despawn();  
-            println!("Weapon 2 was not added and is being despawned");
-        }
+
+//Gives the player weapons the same way picking them up does
+pub fn _equip_player_weapon(
+    key_input: Res<Input<KeyCode>>,
+    mut pickup_event: EventWriter<ItemPickedUpEvent>,
+) {
+    
+    if key_input.just_pressed(KeyCode::P) {
+        pickup_event.send(ItemPickedUpEvent(PlayerPickupType::Knife));
+    }
+    else if key_input.just_pressed(KeyCode::O) {
+        pickup_event.send(ItemPickedUpEvent(PlayerPickupType::Weapon(SWORD_ID.to_string())));
     }
 }
 
@@ -372,6 +395,7 @@ pub fn _player_damage(
             event.send(HealthChangeEvent {
                 entity: player,
                 change_type: HealthChangeType::Add{value: -10},
+                knockback: None,
             })
         }
     }
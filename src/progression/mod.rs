@@ -0,0 +1,33 @@
+//===============================================================
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::RegisterLdtkObjects;
+
+use crate::general::GameState;
+
+pub mod progression_components;
+mod progression_systems;
+
+//===============================================================
+
+pub struct ProgressionPlugin;
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(progression_components::ProgressFlags::default())
+
+            .register_ldtk_entity::<progression_components::GateBundle>("Gate")
+            .register_ldtk_entity::<progression_components::SwitchBundle>("Switch")
+
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(progression_systems::set_item_flags)
+                    .with_system(progression_systems::pull_switch)
+                    .with_system(progression_systems::update_switches)
+                    .with_system(progression_systems::open_gates)
+            )
+        ;
+    }
+}
+
+//===============================================================
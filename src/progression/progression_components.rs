@@ -0,0 +1,238 @@
+//===============================================================
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{LayerInstance, EntityInstance, prelude::{TilesetDefinition, LdtkEntity}};
+
+use crate::{
+    general::{general_components::EntityIid, tools::get_string_field},
+    physics::physics_components::ColliderBundle,
+    interaction::interaction_components::Interactable,
+};
+
+//===============================================================
+
+pub const GATE_OPEN_TIME: f32 = 0.6;
+pub const SWITCH_INTERACT_DISTANCE: f32 = 30.;
+
+pub const GATE_COLOR: Color = Color::GRAY;
+pub const SWITCH_OFF_COLOR: Color = Color::YELLOW;
+pub const SWITCH_ON_COLOR: Color = Color::GREEN;
+
+//===============================================================
+
+//Named things that have happened in the world (items found, bosses beaten, switches pulled, gates opened).
+//Saved with the rest of the players progress
+#[derive(Default, Clone, Debug)]
+pub struct ProgressFlags(pub HashSet<String>);
+impl ProgressFlags {
+    pub fn item(name: &str) -> String {
+        format!("item:{}", name)
+    }
+    pub fn boss(name: &str) -> String {
+        format!("boss:{}", name)
+    }
+    pub fn switch(id: &str) -> String {
+        format!("switch:{}", id)
+    }
+    pub fn gate(iid: &EntityIid) -> String {
+        format!("gate:{}:{}", iid.level_uid, iid.iid)
+    }
+
+    pub fn contains(&self, flag: &str) -> bool {
+        self.0.contains(flag)
+    }
+    //Returns true if the flag wasn't already set
+    pub fn set(&mut self, flag: String) -> bool {
+        self.0.insert(flag)
+    }
+}
+
+//===============================================================
+
+//Written in LDtk as "Kind:Name", e.g. "Ability:Cling", "Item:Gem", "Boss:Golem" or "Switch:cave_lever"
+#[derive(Clone, Debug, PartialEq)]
+pub enum GateRequirement {
+    Ability(String),
+    Item(String),
+    Boss(String),
+    Switch(String),
+}
+impl GateRequirement {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (kind, name) = value.split_once(':')?;
+        let name = name.trim().to_string();
+
+        match kind.trim() {
+            "Ability"   => Some(GateRequirement::Ability(name)),
+            "Item"      => Some(GateRequirement::Item(name)),
+            "Boss"      => Some(GateRequirement::Boss(name)),
+            "Switch"    => Some(GateRequirement::Switch(name)),
+            _           => None,
+        }
+    }
+
+    //Abilities aren't flags, so the caller says which ones the player has
+    pub fn is_satisfied(&self, flags: &ProgressFlags, can_sprint: bool, can_cling: bool) -> bool {
+        match self {
+            GateRequirement::Ability(ability) => {
+                match ability.as_str() {
+                    "Sprint"    => can_sprint,
+                    "Cling"     => can_cling,
+                    _           => false,
+                }
+            },
+            GateRequirement::Item(item)     => flags.contains(&ProgressFlags::item(item)),
+            GateRequirement::Boss(boss)     => flags.contains(&ProgressFlags::boss(boss)),
+            GateRequirement::Switch(id)     => flags.contains(&ProgressFlags::switch(id)),
+        }
+    }
+}
+
+//===============================================================
+
+//A closed gate. The component is removed once the gate starts opening
+#[derive(Component, Default, Clone, Debug)]
+pub struct Gate {
+    pub requirement: Option<GateRequirement>,
+}
+
+#[derive(Bundle, Default, Clone)]
+pub struct GateBundle {
+    gate: Gate,
+    iid: EntityIid,
+    #[bundle]
+    sprite: SpriteBundle,
+    #[bundle]
+    collider: ColliderBundle,
+}
+impl LdtkEntity for GateBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+
+        let requirement = get_string_field(entity_instance, "Requires").and_then(|value| {
+            let requirement = GateRequirement::parse(&value);
+            if requirement.is_none() {
+                println!("Gate has an invalid requirement: {}", value);
+            }
+            requirement
+        });
+
+        let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+
+        GateBundle {
+            gate: Gate {
+                requirement,
+            },
+            iid: EntityIid::from_entity_info(entity_instance, layer_instance),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: GATE_COLOR,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            collider: ColliderBundle::wall(size.x, size.y),
+        }
+    }
+}
+
+//===============================================================
+
+#[derive(Component, Default, Clone, Debug)]
+pub struct Switch(pub String);
+
+#[derive(Bundle, Default, Clone)]
+pub struct SwitchBundle {
+    switch: Switch,
+    interactable: Interactable,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+impl LdtkEntity for SwitchBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+
+        let id = get_string_field(entity_instance, "SwitchId").unwrap_or_default();
+
+        SwitchBundle {
+            switch: Switch(id),
+            interactable: Interactable::new("Pull switch", SWITCH_INTERACT_DISTANCE),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: SWITCH_OFF_COLOR,
+                    custom_size: Some(Vec2::new(8., 16.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_kind() {
+        assert_eq!(GateRequirement::parse("Ability:Cling"), Some(GateRequirement::Ability("Cling".to_string())));
+        assert_eq!(GateRequirement::parse("Item:Gem"), Some(GateRequirement::Item("Gem".to_string())));
+        assert_eq!(GateRequirement::parse("Boss:Bat Queen"), Some(GateRequirement::Boss("Bat Queen".to_string())));
+        assert_eq!(GateRequirement::parse(" Switch : cave_lever "), Some(GateRequirement::Switch("cave_lever".to_string())));
+    }
+
+    #[test]
+    fn rejects_unknown_or_missing_kinds() {
+        assert_eq!(GateRequirement::parse("Key:Red"), None);
+        assert_eq!(GateRequirement::parse("Gem"), None);
+        assert_eq!(GateRequirement::parse(""), None);
+    }
+
+    #[test]
+    fn flag_requirements_need_their_flag() {
+        let mut flags = ProgressFlags::default();
+        let requirements = [
+            (GateRequirement::Item("Gem".to_string()), ProgressFlags::item("Gem")),
+            (GateRequirement::Boss("Bat Queen".to_string()), ProgressFlags::boss("Bat Queen")),
+            (GateRequirement::Switch("cave_lever".to_string()), ProgressFlags::switch("cave_lever")),
+        ];
+
+        for (requirement, _) in requirements.iter() {
+            assert!(!requirement.is_satisfied(&flags, true, true));
+        }
+        for (requirement, flag) in requirements.iter() {
+            flags.set(flag.clone());
+            assert!(requirement.is_satisfied(&flags, false, false));
+        }
+    }
+
+    #[test]
+    fn ability_requirements_need_the_ability() {
+        let flags = ProgressFlags::default();
+        let sprint = GateRequirement::Ability("Sprint".to_string());
+        let cling = GateRequirement::Ability("Cling".to_string());
+
+        assert!(sprint.is_satisfied(&flags, true, false));
+        assert!(!sprint.is_satisfied(&flags, false, true));
+        assert!(cling.is_satisfied(&flags, false, true));
+        assert!(!cling.is_satisfied(&flags, true, false));
+        assert!(!GateRequirement::Ability("Fly".to_string()).is_satisfied(&flags, true, true));
+    }
+}
+
+//===============================================================
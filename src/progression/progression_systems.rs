@@ -0,0 +1,104 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+use super::progression_components::*;
+
+use crate::{
+    general::general_components::{EntityIid, FadeInOut},
+    physics::physics_components::ColliderBundle,
+    player::player_components::{Player, PlayerSprint, PlayerWallCling},
+    interaction::interaction_components::{Interactable, InteractEvent},
+    world::ItemPickedUpEvent,
+};
+
+//===============================================================
+
+pub fn set_item_flags(
+    mut pickup_event: EventReader<ItemPickedUpEvent>,
+    mut flags: ResMut<ProgressFlags>,
+) {
+    for event in pickup_event.iter() {
+        flags.set(ProgressFlags::item(event.0.item_name()));
+    }
+}
+
+//===============================================================
+
+pub fn open_gates(
+    gate_query: Query<(Entity, &Gate, &EntityIid)>,
+    player_query: Query<(&PlayerSprint, &PlayerWallCling), With<Player>>,
+    mut flags: ResMut<ProgressFlags>,
+    mut commands: Commands,
+) {
+    let (can_sprint, can_cling) = match player_query.get_single() {
+        Ok((sprint, wall_cling)) => (sprint.can_sprint, wall_cling.can_cling),
+        Err(_) => (false, false),
+    };
+
+    for (entity, gate, iid) in gate_query.iter() {
+
+        let gate_flag = ProgressFlags::gate(iid);
+
+        //Gate was opened before, so it shouldn't be here at all
+        if flags.contains(&gate_flag) {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let satisfied = match &gate.requirement {
+            Some(requirement) => requirement.is_satisfied(&flags, can_sprint, can_cling),
+            None => false,
+        };
+        if !satisfied {
+            continue;
+        }
+
+        flags.set(gate_flag);
+
+        //Let the player through straight away and fade the gate out
+        commands.entity(entity)
+            .remove::<Gate>()
+            .remove_bundle::<ColliderBundle>()
+            .insert(FadeInOut {
+                timer: Timer::from_seconds(GATE_OPEN_TIME, false),
+                from: 1.,
+                to: 0.,
+                remove_on_finish: true,
+                remove_component_on_finish: false,
+            });
+    }
+}
+
+//===============================================================
+
+pub fn pull_switch(
+    switch_query: Query<&Switch>,
+    mut interact_event: EventReader<InteractEvent>,
+    mut flags: ResMut<ProgressFlags>,
+) {
+    for interaction in interact_event.iter() {
+        if let Ok(switch) = switch_query.get(interaction.0) {
+            flags.set(ProgressFlags::switch(&switch.0));
+        }
+    }
+}
+
+//Also covers switches that were pulled in an earlier session
+pub fn update_switches(
+    mut switch_query: Query<(&Switch, &mut Interactable, &mut Sprite)>,
+    flags: Res<ProgressFlags>,
+) {
+    for (switch, mut interactable, mut sprite) in switch_query.iter_mut() {
+
+        let pulled = flags.contains(&ProgressFlags::switch(&switch.0));
+        if interactable.enabled != pulled {
+            continue;
+        }
+
+        interactable.enabled = !pulled;
+        sprite.color = if pulled { SWITCH_ON_COLOR } else { SWITCH_OFF_COLOR };
+    }
+}
+
+//===============================================================
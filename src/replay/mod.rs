@@ -0,0 +1,48 @@
+//===============================================================
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use heron::PhysicsSteps;
+
+use crate::general::general_components::FrameDelta;
+
+pub mod replay_components;
+mod replay_systems;
+
+//===============================================================
+
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+
+        let replay_mode = replay_components::ReplayMode::from_args();
+
+        //Step physics and gameplay by the same amount every frame so a replay doesn't depend on the frame rate
+        if replay_mode.is_active() {
+            let timestep = Duration::from_secs_f64(replay_mode.timestep());
+            app
+                .insert_resource(PhysicsSteps::every_frame(timestep))
+                .insert_resource(FrameDelta::fixed(timestep));
+        }
+
+        app
+            .insert_resource(replay_mode)
+            .add_startup_system_to_stage(StartupStage::PostStartup, replay_systems::setup_replay)
+
+            .add_system_to_stage(CoreStage::PreUpdate, replay_systems::record_input
+                .label("RecordInput")
+                .after("UpdateActionInput")
+                .before("FindInteractionTarget")
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, replay_systems::replay_input
+                .label("ReplayInput")
+                .after("UpdateActionInput")
+                .before("FindInteractionTarget")
+            )
+            .add_system_to_stage(CoreStage::Last, replay_systems::write_recording)
+        ;
+    }
+}
+
+//===============================================================
@@ -0,0 +1,101 @@
+//===============================================================
+
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::{controls::controls_components::PlayerAction, general::tools::load_ron};
+
+//===============================================================
+
+//Physics advances by exactly this much every frame while recording or replaying
+pub const REPLAY_TIMESTEP: f64 = 1. / 60.;
+
+//How close the replayed end position has to be to the recorded one to count as a match
+pub const REPLAY_POSITION_TOLERANCE: f32 = 0.5;
+
+//===============================================================
+
+//Every action held on every frame of a run. Written with --record <file> and played back with --replay <file>
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct InputRecording {
+    pub level_uid: i32,
+    pub timestep: f64,
+    pub frames: Vec<Vec<PlayerAction>>,
+    //Where the player ended up. Used to check a replay still ends in the same place
+    pub final_player_position: Option<(f32, f32)>,
+}
+impl InputRecording {
+    //Whether a run ended where the recorded one did. Recordings without an end position always match
+    pub fn matches(&self, final_position: Option<Vec2>) -> bool {
+        match (self.final_player_position, final_position) {
+            (Some((x, y)), Some(position)) => Vec2::new(x, y).distance(position) <= REPLAY_POSITION_TOLERANCE,
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+}
+
+//===============================================================
+
+pub enum ReplayMode {
+    Off,
+    Recording {
+        path: String,
+        recording: InputRecording,
+    },
+    Replaying {
+        recording: InputRecording,
+        frame: usize,
+    },
+}
+impl ReplayMode {
+    //Reads --record <file> or --replay <file> from the command line
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+
+        for (index, arg) in args.iter().enumerate() {
+            let path = match args.get(index + 1) {
+                Some(path) => path.clone(),
+                None => continue,
+            };
+
+            match arg.as_str() {
+                "--record" => {
+                    return ReplayMode::Recording {
+                        path,
+                        recording: InputRecording {
+                            timestep: REPLAY_TIMESTEP,
+                            ..Default::default()
+                        },
+                    }
+                },
+                "--replay" => {
+                    match load_ron(&path) {
+                        Some(recording) => return ReplayMode::Replaying {
+                            recording,
+                            frame: 0,
+                        },
+                        None => println!("Couldn't open recording {}", path),
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        ReplayMode::Off
+    }
+
+    pub fn is_active(&self) -> bool {
+        !matches!(self, ReplayMode::Off)
+    }
+
+    pub fn timestep(&self) -> f64 {
+        match self {
+            ReplayMode::Replaying { recording, .. } => recording.timestep,
+            _ => REPLAY_TIMESTEP,
+        }
+    }
+}
+
+//===============================================================
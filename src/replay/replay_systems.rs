@@ -0,0 +1,122 @@
+//===============================================================
+
+use bevy::{prelude::*, app::AppExit};
+use bevy_ecs_ldtk::prelude::*;
+
+use super::replay_components::*;
+
+use crate::{
+    controls::controls_components::{ActionInput, ActionInputSource},
+    player::player_components::Player,
+    save::save_components::{SaveData, SavingEnabled},
+    world::ActiveCheckpoint,
+    ui::CoinsCollected,
+    progression::progression_components::ProgressFlags,
+    map::map_components::VisitedLevels,
+    non_player::non_player_components::DefeatedEnemies,
+    general::{GameState, tools::save_ron},
+};
+
+//===============================================================
+
+//Runs after the save file is loaded so recorded and replayed runs always start from a fresh game
+pub fn setup_replay(
+    mut replay_mode: ResMut<ReplayMode>,
+    mut input_source: ResMut<ActionInputSource>,
+    mut current_level: ResMut<LevelSelection>,
+    mut saving_enabled: ResMut<SavingEnabled>,
+    mut save_data: ResMut<SaveData>,
+    mut coins: ResMut<CoinsCollected>,
+    mut checkpoint: ResMut<Option<ActiveCheckpoint>>,
+    mut flags: ResMut<ProgressFlags>,
+    mut visited: ResMut<VisitedLevels>,
+    mut defeated: ResMut<DefeatedEnemies>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if !replay_mode.is_active() {
+        return;
+    }
+
+    saving_enabled.0 = false;
+    *save_data = SaveData::default();
+    coins.0 = 0;
+    *checkpoint = None;
+    *flags = ProgressFlags::default();
+    *visited = VisitedLevels::default();
+    *defeated = DefeatedEnemies::default();
+
+    //Skip the main menu and start playing straight away
+    let _ = game_state.set(GameState::Playing);
+
+    match &mut *replay_mode {
+        ReplayMode::Recording { recording, .. } => {
+            recording.level_uid = match *current_level {
+                LevelSelection::Uid(uid) => uid,
+                _ => 0,
+            };
+        },
+        ReplayMode::Replaying { recording, .. } => {
+            *current_level = LevelSelection::Uid(recording.level_uid);
+            *input_source = ActionInputSource::External;
+        },
+        ReplayMode::Off => {},
+    }
+}
+
+//===============================================================
+
+pub fn record_input(
+    mut replay_mode: ResMut<ReplayMode>,
+    action_input: Res<ActionInput>,
+) {
+    if let ReplayMode::Recording { recording, .. } = &mut *replay_mode {
+        recording.frames.push(action_input.get_pressed().iter().copied().collect());
+    }
+}
+
+pub fn replay_input(
+    mut replay_mode: ResMut<ReplayMode>,
+    mut action_input: ResMut<ActionInput>,
+    player_query: Query<&Transform, With<Player>>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if let ReplayMode::Replaying { recording, frame } = &mut *replay_mode {
+
+        if let Some(actions) = recording.frames.get(*frame) {
+            action_input.update(actions.iter().copied().collect());
+            *frame += 1;
+            return;
+        }
+
+        //Replay finished. A run that ends somewhere else exits with an error so scripts can catch it
+        let final_position = player_query.get_single().ok().map(|transform| transform.translation.truncate());
+        if !recording.matches(final_position) {
+            std::process::exit(1);
+        }
+
+        app_exit.send(AppExit);
+    }
+}
+
+//Runs in the last stage so it still sees the exit event from closing the window
+pub fn write_recording(
+    mut replay_mode: ResMut<ReplayMode>,
+    mut app_exit: EventReader<AppExit>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if app_exit.iter().count() == 0 {
+        return;
+    }
+
+    if let ReplayMode::Recording { path, recording } = &mut *replay_mode {
+
+        recording.final_player_position = player_query
+            .get_single()
+            .ok()
+            .map(|transform| (transform.translation.x, transform.translation.y));
+
+        save_ron(recording, path);
+    }
+}
+
+//===============================================================
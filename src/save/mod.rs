@@ -0,0 +1,27 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+pub mod save_components;
+mod save_systems;
+
+//===============================================================
+
+pub struct SavePlugin;
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(save_components::SaveData::default())
+            .insert_resource(save_components::SavingEnabled(true))
+            .add_startup_system(save_systems::load_save_file)
+
+            .add_system(save_systems::restore_player)
+            .add_system(save_systems::restore_pickups)
+            .add_system(save_systems::track_collected_pickups)
+
+            .add_system_to_stage(CoreStage::PostUpdate, save_systems::autosave)
+        ;
+    }
+}
+
+//===============================================================
@@ -0,0 +1,84 @@
+//===============================================================
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    world::ActiveCheckpoint,
+    general::general_components::EntityIid,
+    weapons::weapon_prefabs::{SWORD_ID, THROWING_KNIFE_ID},
+};
+
+//===============================================================
+
+pub const SAVE_FILE_PATH: &str = "save.ron";
+
+//===============================================================
+
+//Turned off for runs that shouldn't touch the players save file, like input replays
+pub struct SavingEnabled(pub bool);
+
+//Everything about the players progression that should persist between sessions
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct SaveData {
+    pub level_uid: i32,
+    pub player_position: Option<(f32, f32)>,
+    pub health: Option<i32>,
+    pub checkpoint: Option<ActiveCheckpoint>,
+
+    pub can_sprint: bool,
+    pub can_cling: bool,
+    pub weapon_slot1: Option<String>,
+    pub weapon_slot2: Option<String>,
+
+    pub coins: usize,
+    //Level uid -> iids of the pickups collected in that level
+    pub collected_pickups: HashMap<i32, HashSet<String>>,
+
+    //Older saves don't have flags
+    #[serde(default)]
+    pub flags: HashSet<String>,
+    #[serde(default)]
+    pub visited_levels: HashSet<i32>,
+    //Enemies that never respawn, like bosses
+    #[serde(default)]
+    pub defeated_enemies: HashSet<EntityIid>,
+}
+impl SaveData {
+    pub fn has_player(&self) -> bool {
+        self.player_position.is_some()
+    }
+
+    //Brings saves from older versions up to date
+    pub fn migrate(&mut self) {
+        SaveData::migrate_weapon_id(&mut self.weapon_slot1);
+        SaveData::migrate_weapon_id(&mut self.weapon_slot2);
+    }
+
+    //Weapon ids were the names of the built in weapons before weapons were loaded from files
+    fn migrate_weapon_id(id: &mut Option<String>) {
+        let new_id = match id.as_deref() {
+            Some("Sword") => SWORD_ID,
+            Some("ThrowingKnife") => THROWING_KNIFE_ID,
+            _ => return,
+        };
+        *id = Some(new_id.to_string());
+    }
+
+    pub fn is_pickup_collected(&self, level_uid: i32, iid: &str) -> bool {
+        match self.collected_pickups.get(&level_uid) {
+            Some(collected) => collected.contains(iid),
+            None => false,
+        }
+    }
+    pub fn add_collected_pickup(&mut self, level_uid: i32, iid: String) {
+        self.collected_pickups
+            .entry(level_uid)
+            .or_default()
+            .insert(iid);
+    }
+
+}
+
+//===============================================================
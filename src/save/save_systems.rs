@@ -0,0 +1,178 @@
+//===============================================================
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use super::save_components::*;
+
+use crate::{
+    general::tools::{load_ron, save_ron},
+    player::player_components::{Player, PlayerSprint, PlayerWallCling},
+    weapons::{weapon_components::{WeaponInventory, WeaponId}, weapon_prefabs::WeaponSpawnerBundle},
+    general::general_components::{Health, EntityIid},
+    world::{PickupCollected, ItemPickedUpEvent, LevelChangedEvent, ActiveCheckpoint, CheckpointActivatedEvent},
+    ui::CoinsCollected,
+    progression::progression_components::ProgressFlags,
+    map::map_components::VisitedLevels,
+    non_player::non_player_components::DefeatedEnemies,
+};
+
+//===============================================================
+
+pub fn load_save_file(
+    mut save_data: ResMut<SaveData>,
+    mut coins: ResMut<CoinsCollected>,
+    mut checkpoint: ResMut<Option<ActiveCheckpoint>>,
+    mut flags: ResMut<ProgressFlags>,
+    mut visited: ResMut<VisitedLevels>,
+    mut defeated: ResMut<DefeatedEnemies>,
+) {
+    if let Some(mut save) = load_ron::<SaveData>(SAVE_FILE_PATH) {
+        save.migrate();
+
+        coins.0 = save.coins;
+        *checkpoint = save.checkpoint.clone();
+        flags.0 = save.flags.clone();
+        visited.0 = save.visited_levels.clone();
+        defeated.permanent = save.defeated_enemies.clone();
+        *save_data = save;
+    }
+}
+
+//===============================================================
+
+pub fn restore_player(
+    mut player_query: Query<(
+        Entity, &mut Transform, &mut Health, &mut PlayerSprint, &mut PlayerWallCling, &mut WeaponInventory
+    ), Added<Player>>,
+    save_data: Res<SaveData>,
+    mut current_level: ResMut<LevelSelection>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+) {
+    if !save_data.has_player() {
+        return;
+    }
+
+    for (player, mut transform, mut health, mut sprint, mut wall_cling, mut inventory) in player_query.iter_mut() {
+
+        if let Some((x, y)) = save_data.player_position {
+            transform.translation.x = x;
+            transform.translation.y = y;
+        }
+        if let Some(saved_health) = save_data.health {
+            health.set_health(saved_health);
+        }
+
+        sprint.can_sprint = save_data.can_sprint;
+        wall_cling.can_cling = save_data.can_cling;
+
+        if let Some(id) = &save_data.weapon_slot1 {
+            let new_weapon = commands.spawn_bundle(WeaponSpawnerBundle::new(id, &assets, true)).id();
+            if inventory.add_slot1_weapon(new_weapon) {
+                commands.entity(player).add_child(new_weapon);
+            }
+            else {
+                commands.entity(new_weapon).despawn();
+            }
+        }
+        if let Some(id) = &save_data.weapon_slot2 {
+            let new_weapon = commands.spawn_bundle(WeaponSpawnerBundle::new(id, &assets, true)).id();
+            if inventory.add_slot2_weapon(new_weapon) {
+                commands.entity(player).add_child(new_weapon);
+            }
+            else {
+                commands.entity(new_weapon).despawn();
+            }
+        }
+
+        //The player is only spawned in its starting level, so make sure the saved one gets loaded
+        *current_level = LevelSelection::Uid(save_data.level_uid);
+    }
+}
+
+pub fn restore_pickups(
+    mut pickup_query: Query<(&EntityIid, &mut PickupCollected, &mut Visibility), Added<PickupCollected>>,
+    save_data: Res<SaveData>,
+) {
+    for (iid, mut collected, mut visible) in pickup_query.iter_mut() {
+
+        if save_data.is_pickup_collected(iid.level_uid, &iid.iid) {
+            collected.0 = true;
+            visible.is_visible = false;
+        }
+    }
+}
+
+pub fn track_collected_pickups(
+    pickup_query: Query<(&EntityIid, &PickupCollected), Changed<PickupCollected>>,
+    mut save_data: ResMut<SaveData>,
+) {
+    for (iid, collected) in pickup_query.iter() {
+        if collected.0 {
+            save_data.add_collected_pickup(iid.level_uid, iid.iid.clone());
+        }
+    }
+}
+
+//===============================================================
+
+//Runs in PostUpdate so newly equipped weapons have been spawned before they are saved
+pub fn autosave(
+    player_query: Query<(&Transform, &Health, &PlayerSprint, &PlayerWallCling, &WeaponInventory), With<Player>>,
+    weapon_query: Query<&WeaponId>,
+    mut level_changed_event: EventReader<LevelChangedEvent>,
+    mut pickup_event: EventReader<ItemPickedUpEvent>,
+    mut checkpoint_event: EventReader<CheckpointActivatedEvent>,
+    mut save_data: ResMut<SaveData>,
+    saving_enabled: Res<SavingEnabled>,
+    current_level: Res<LevelSelection>,
+    coins: Res<CoinsCollected>,
+    checkpoint: Res<Option<ActiveCheckpoint>>,
+    flags: Res<ProgressFlags>,
+    visited: Res<VisitedLevels>,
+    defeated: Res<DefeatedEnemies>,
+) {
+    if !saving_enabled.0 {
+        return;
+    }
+
+    let level_changed = level_changed_event.iter().count() > 0;
+    let item_picked_up = pickup_event.iter().count() > 0;
+    let checkpoint_activated = checkpoint_event.iter().count() > 0;
+    let flags_changed = flags.is_changed();
+    let boss_defeated = defeated.is_changed() && defeated.permanent != save_data.defeated_enemies;
+
+    if !level_changed && !item_picked_up && !checkpoint_activated && !flags_changed && !boss_defeated {
+        return;
+    }
+
+    if let Ok((transform, health, sprint, wall_cling, inventory)) = player_query.get_single() {
+
+        let weapon_id = |slot: &Option<Entity>| -> Option<String> {
+            match slot {
+                Some(weapon) => weapon_query.get(*weapon).ok().map(|id| id.0.clone()),
+                None => None,
+            }
+        };
+
+        if let LevelSelection::Uid(uid) = *current_level {
+            save_data.level_uid = uid;
+        }
+        save_data.player_position = Some((transform.translation.x, transform.translation.y));
+        save_data.health = Some(health.get_health());
+        save_data.checkpoint = checkpoint.clone();
+        save_data.can_sprint = sprint.can_sprint;
+        save_data.can_cling = wall_cling.can_cling;
+        save_data.weapon_slot1 = weapon_id(inventory.get_slot1());
+        save_data.weapon_slot2 = weapon_id(inventory.get_slot2());
+        save_data.coins = coins.0;
+        save_data.flags = flags.0.clone();
+        save_data.visited_levels = visited.0.clone();
+        save_data.defeated_enemies = defeated.permanent.clone();
+
+        save_ron(&*save_data, SAVE_FILE_PATH);
+    }
+}
+
+//===============================================================
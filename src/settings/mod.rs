@@ -0,0 +1,53 @@
+//===============================================================
+
+use bevy::prelude::*;
+
+use crate::general::{GameState, tools::load_ron_or_default};
+
+pub mod settings_components;
+mod settings_systems;
+
+use settings_components::*;
+
+//===============================================================
+
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+
+        //Read straight away since MSAA can't be changed once rendering has started
+        let settings: Settings = load_ron_or_default(SETTINGS_FILE_PATH);
+
+        app
+            .insert_resource(Msaa { samples: settings.msaa_samples })
+            .insert_resource(settings)
+            .insert_resource(SettingsOpen(false))
+            .insert_resource(RebindingAction::default())
+
+            .add_system(settings_systems::apply_settings)
+            .add_system_to_stage(CoreStage::PreUpdate, settings_systems::capture_rebind
+                .after("UpdateActionInput")
+                .before("FindInteractionTarget")
+            )
+
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(settings_systems::show_settings)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Paused)
+                    .with_system(settings_systems::close_settings)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu)
+                    .with_system(settings_systems::show_settings)
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu)
+                    .with_system(settings_systems::close_settings)
+            )
+        ;
+    }
+}
+
+//===============================================================
@@ -0,0 +1,103 @@
+//===============================================================
+
+use bevy::{math::Vec2, window::WindowMode};
+use serde::{Serialize, Deserialize};
+
+use crate::controls::controls_components::PlayerAction;
+
+//===============================================================
+
+pub const SETTINGS_FILE_PATH: &str = "settings.ron";
+
+//Sample counts every backend supports
+pub const MSAA_OPTIONS: [u32; 2] = [1, 4];
+
+pub const MIN_UI_SCALE: f32 = 0.5;
+pub const MAX_UI_SCALE: f32 = 2.;
+
+//The camera always keeps this aspect ratio, the camera scale setting is how many of these it shows
+pub const ASPECT_RATIO_WIDTH: f32 = 16.;
+pub const ASPECT_RATIO_HEIGHT: f32 = 10.;
+
+pub const MIN_CAMERA_SCALE: f32 = 25.;
+pub const MAX_CAMERA_SCALE: f32 = 45.;
+
+//===============================================================
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsWindowMode {
+    Windowed,
+    BorderlessFullscreen,
+    Fullscreen,
+}
+impl SettingsWindowMode {
+    pub const ALL: [SettingsWindowMode; 3] = [
+        SettingsWindowMode::Windowed,
+        SettingsWindowMode::BorderlessFullscreen,
+        SettingsWindowMode::Fullscreen,
+    ];
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            SettingsWindowMode::Windowed => WindowMode::Windowed,
+            SettingsWindowMode::BorderlessFullscreen => WindowMode::BorderlessFullscreen,
+            SettingsWindowMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+//Player settings. Loaded from and saved to SETTINGS_FILE_PATH, key bindings are kept in the controls file
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    //Only applied at startup
+    pub msaa_samples: u32,
+    pub window_mode: SettingsWindowMode,
+    pub ui_scale: f32,
+    pub camera_scale: f32,
+
+    //All volumes are 0 to 1, and music and effects are scaled by the master volume
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+
+    pub screen_shake: bool,
+}
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            msaa_samples: 4,
+            window_mode: SettingsWindowMode::Windowed,
+            ui_scale: 1.,
+            camera_scale: 35.,
+            master_volume: 1.,
+            music_volume: 0.8,
+            effects_volume: 1.,
+            screen_shake: true,
+        }
+    }
+}
+impl Settings {
+    pub fn music_volume(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+    pub fn effects_volume(&self) -> f32 {
+        self.master_volume * self.effects_volume
+    }
+
+    //Size of the game camera in world units
+    pub fn camera_size(&self) -> Vec2 {
+        Vec2::new(ASPECT_RATIO_WIDTH, ASPECT_RATIO_HEIGHT) * self.camera_scale
+    }
+}
+
+//===============================================================
+
+//Whether the settings window is open on top of a menu
+pub struct SettingsOpen(pub bool);
+
+//The action waiting for the player to press a new input for it
+#[derive(Default)]
+pub struct RebindingAction(pub Option<PlayerAction>);
+
+//===============================================================
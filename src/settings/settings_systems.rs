@@ -0,0 +1,199 @@
+//===============================================================
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContext, EguiSettings, egui::{self, Align2}};
+
+use super::settings_components::*;
+
+use crate::{
+    controls::controls_components::{ActionInput, ActionMap, InputBinding, PlayerAction, CONTROLS_FILE_PATH},
+    general::tools::save_ron,
+};
+
+//===============================================================
+
+pub fn apply_settings(
+    settings: Res<Settings>,
+    mut windows: ResMut<Windows>,
+    mut egui_settings: ResMut<EguiSettings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let Some(window) = windows.get_primary_mut() {
+        let window_mode = settings.window_mode.window_mode();
+        if window.mode() != window_mode {
+            window.set_mode(window_mode);
+        }
+    }
+
+    egui_settings.scale_factor = settings.ui_scale as f64;
+}
+
+//===============================================================
+
+//Binds the next key or gamepad button pressed to the action being rebound. Escape cancels
+pub fn capture_rebind(
+    mut rebinding: ResMut<RebindingAction>,
+    mut action_map: ResMut<ActionMap>,
+    mut action_input: ResMut<ActionInput>,
+    key_input: Res<Input<KeyCode>>,
+    button_input: Res<Input<GamepadButton>>,
+) {
+    let action = match rebinding.0 {
+        Some(action) => action,
+        None => return,
+    };
+
+    //Nothing else should react to the press being bound
+    action_input.consume_all();
+
+    let binding = if key_input.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+    else if let Some(key) = key_input.get_just_pressed().next() {
+        InputBinding::Key(*key)
+    }
+    else if let Some(button) = button_input.get_just_pressed().next() {
+        InputBinding::GamepadButton(button.1)
+    }
+    else {
+        return;
+    };
+
+    let bindings = action_map.bindings.entry(action).or_default();
+    if !bindings.contains(&binding) {
+        bindings.push(binding);
+    }
+    rebinding.0 = None;
+}
+
+//===============================================================
+
+pub fn show_settings(
+    mut egui_context: ResMut<EguiContext>,
+    mut settings_open: ResMut<SettingsOpen>,
+    mut settings: ResMut<Settings>,
+    mut action_map: ResMut<ActionMap>,
+    mut rebinding: ResMut<RebindingAction>,
+) {
+    if !settings_open.0 {
+        return;
+    }
+
+    //Edit a copy so change detection only fires when something actually changed
+    let mut edited = settings.clone();
+    let mut removed_binding = None;
+    let mut reset_controls = false;
+    let mut close = false;
+
+    egui::Window::new("Settings")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::RIGHT_CENTER, egui::Vec2::new(-50., 0.))
+        .show(egui_context.ctx_mut(), |ui| {
+
+        ui.heading("Video");
+        egui::ComboBox::from_label("Window mode")
+            .selected_text(format!("{:?}", edited.window_mode))
+            .show_ui(ui, |ui| {
+            for mode in SettingsWindowMode::ALL {
+                ui.selectable_value(&mut edited.window_mode, mode, format!("{:?}", mode));
+            }
+        });
+        egui::ComboBox::from_label("MSAA (after restart)")
+            .selected_text(format!("{}x", edited.msaa_samples))
+            .show_ui(ui, |ui| {
+            for samples in MSAA_OPTIONS {
+                ui.selectable_value(&mut edited.msaa_samples, samples, format!("{}x", samples));
+            }
+        });
+        ui.add(egui::Slider::new(&mut edited.ui_scale, MIN_UI_SCALE..=MAX_UI_SCALE).text("UI scale"));
+        ui.add(egui::Slider::new(&mut edited.camera_scale, MIN_CAMERA_SCALE..=MAX_CAMERA_SCALE).text("Camera size"));
+
+        ui.heading("Audio");
+        ui.add(egui::Slider::new(&mut edited.master_volume, 0.0..=1.0).text("Master volume"));
+        ui.add(egui::Slider::new(&mut edited.music_volume, 0.0..=1.0).text("Music volume"));
+        ui.add(egui::Slider::new(&mut edited.effects_volume, 0.0..=1.0).text("Effects volume"));
+
+        ui.heading("Gameplay");
+        ui.checkbox(&mut edited.screen_shake, "Screen shake");
+
+        ui.heading("Controls");
+        ui.label("Click a binding to remove it");
+        egui::Grid::new("Controls").show(ui, |ui| {
+            for action in PlayerAction::ALL {
+                ui.label(format!("{:?}", action));
+
+                ui.horizontal(|ui| {
+                    for (index, binding) in action_map.get_bindings(action).iter().enumerate() {
+                        if ui.small_button(binding.display_name()).clicked() {
+                            removed_binding = Some((action, index));
+                        }
+                    }
+
+                    if rebinding.0 == Some(action) {
+                        ui.label("Press a key or button...");
+                    }
+                    else if ui.small_button("+").clicked() {
+                        rebinding.0 = Some(action);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Reset controls").clicked() {
+                reset_controls = true;
+            }
+            if ui.button("Close").clicked() {
+                close = true;
+            }
+        });
+    });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+
+    if let Some((action, index)) = removed_binding {
+        if let Some(bindings) = action_map.bindings.get_mut(&action) {
+            //Without a pause binding there is no way back to the menu
+            if action != PlayerAction::Pause || bindings.len() > 1 {
+                bindings.remove(index);
+            }
+        }
+    }
+    if reset_controls {
+        *action_map = ActionMap::default();
+        rebinding.0 = None;
+    }
+
+    if close {
+        settings_open.0 = false;
+        rebinding.0 = None;
+        save_ron(&*settings, SETTINGS_FILE_PATH);
+        save_ron(&*action_map, CONTROLS_FILE_PATH);
+    }
+}
+
+//Saves anything that was changed if the menu the settings were opened from closes
+pub fn close_settings(
+    mut settings_open: ResMut<SettingsOpen>,
+    mut rebinding: ResMut<RebindingAction>,
+    settings: Res<Settings>,
+    action_map: Res<ActionMap>,
+) {
+    if settings_open.0 {
+        save_ron(&*settings, SETTINGS_FILE_PATH);
+        save_ron(&*action_map, CONTROLS_FILE_PATH);
+    }
+
+    settings_open.0 = false;
+    rebinding.0 = None;
+}
+
+//===============================================================
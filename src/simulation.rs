@@ -0,0 +1,222 @@
+//===============================================================
+
+use std::time::Duration;
+
+use bevy::{prelude::*, asset::AssetPlugin, input::InputPlugin, transform::TransformPlugin};
+use heron::{prelude::*, PhysicsSteps};
+use bevy_ecs_ldtk::{LdtkLoader, prelude::{LevelSelection, LdtkAsset, LdtkLevel}};
+
+use crate::{
+    animation, physics, player, non_player, navigation, weapons, general, progression, boss, transition, interaction,
+    world::{self, LevelWallGrids, LevelChangedEvent, CheckpointActivatedEvent, RetryEvent, ItemPickedUpEvent, SnapCamera, ActiveCheckpoint},
+    settings::settings_components::Settings,
+    ui::{Popups, ShowPopup},
+    non_player::{non_player_definitions::{EnemyDefinition, EnemyRespawn}, non_player_prefabs::EnemySpawner},
+    physics::physics_components::{ColliderBundle, GRAVITY},
+    player::player_components::PlayerBundle,
+    controls::controls_components::{ActionInput, ActionMap, ActionInputSource, PlayerAction},
+    general::{tools::load_ron, general_components::{FrameDelta, EntityIid}},
+    replay::replay_components::InputRecording,
+};
+
+//===============================================================
+
+//Every frame of a headless app advances physics and gameplay by exactly this much,
+//however long the frame really took
+pub const SIMULATION_TIMESTEP: f64 = 1. / 60.;
+
+//===============================================================
+
+//Builds an app with the gameplay plugins but no window, renderer, LDtk world or UI.
+//Used by integration tests to spawn entities, step frames and check the results
+pub fn headless_app() -> App {
+    let mut app = App::new();
+
+    app
+        .add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(InputPlugin)
+        .add_asset::<Image>()
+        .add_asset::<TextureAtlas>()
+        //Transitions find where they lead in the project file, which can be loaded with load_world
+        //Levels are never spawned, but the project file loads them along with it
+        .add_asset::<LdtkAsset>()
+        .add_asset::<LdtkLevel>()
+        .init_asset_loader::<LdtkLoader>()
+
+        .add_plugin(PhysicsPlugin::default())
+        .insert_resource(Gravity::from(Vec2::new(0., GRAVITY)))
+        .insert_resource(PhysicsSteps::every_frame(Duration::from_secs_f64(SIMULATION_TIMESTEP)))
+        .insert_resource(FrameDelta::fixed(Duration::from_secs_f64(SIMULATION_TIMESTEP)))
+
+        //Actions are set directly by the test instead of coming from devices
+        .insert_resource(ActionMap::default())
+        .insert_resource(ActionInput::default())
+        .insert_resource(ActionInputSource::External)
+
+        //Shared with the world plugin, which needs LDtk and isn't added here
+        .insert_resource(LevelSelection::default())
+        .insert_resource(LevelWallGrids::default())
+        .insert_resource(SnapCamera(false))
+        .insert_resource::<Option<ActiveCheckpoint>>(None)
+        .insert_resource(Settings::default())
+        .add_event::<LevelChangedEvent>()
+        .add_event::<CheckpointActivatedEvent>()
+        .add_event::<RetryEvent>()
+        .add_event::<ItemPickedUpEvent>()
+        //Popups are never drawn, but bosses and pickups still pick which one would show
+        .insert_resource(Popups::Intro)
+        .add_state(ShowPopup::Hide)
+
+        .add_state(general::GameState::Playing)
+        .add_plugin(general::GeneralPlugin)
+        .add_plugin(animation::AnimationPlugin)
+        .add_plugin(physics::CustomPhysicsPlugin)
+        .add_plugin(player::PlayerPlugin)
+        .add_plugin(weapons::WeaponPlugin)
+        .add_plugin(non_player::NonPlayerPlugin)
+        .add_plugin(navigation::NavigationPlugin)
+        .add_plugin(progression::ProgressionPlugin)
+        .add_plugin(boss::BossPlugin)
+        .add_plugin(transition::TransitionPlugin)
+        .add_plugin(interaction::InteractionPlugin)
+        //Retrying is part of the world plugin, but doesn't need LDtk
+        .add_system(world::respawn_player_at_checkpoint)
+    ;
+
+    app
+}
+
+//Steps physics and gameplay by a fixed amount every frame instead of by the frame rate
+pub fn set_timestep(app: &mut App, timestep: f64) {
+    let timestep = Duration::from_secs_f64(timestep);
+    app
+        .insert_resource(PhysicsSteps::every_frame(timestep))
+        .insert_resource(FrameDelta::fixed(timestep));
+}
+
+//Sets which actions are held. They count as just pressed on the next frame only
+pub fn set_actions(app: &mut App, actions: &[PlayerAction]) {
+    app.world
+        .get_resource_mut::<ActionInput>()
+        .expect("Headless app is missing ActionInput")
+        .update(actions.iter().copied().collect());
+}
+
+//Runs the app for a number of frames, keeping the currently held actions held
+pub fn step_frames(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+
+        let mut action_input = app.world
+            .get_resource_mut::<ActionInput>()
+            .expect("Headless app is missing ActionInput");
+        let held = action_input.get_pressed().clone();
+        action_input.update(held);
+    }
+}
+
+//Plays back every frame of a recording at its timestep and returns where the player ended up.
+//The app should be set up the same way it was when the recording was made
+pub fn replay_recording(app: &mut App, recording: &InputRecording, player: Entity) -> Vec2 {
+    set_timestep(app, recording.timestep);
+
+    for actions in recording.frames.iter() {
+        set_actions(app, actions);
+        app.update();
+    }
+
+    app.world
+        .get::<Transform>(player)
+        .expect("Replayed player is missing a Transform")
+        .translation
+        .truncate()
+}
+
+//===============================================================
+
+//Spawns a static wall the same way walls from the LDtk level are spawned
+pub fn spawn_wall(app: &mut App, position: Vec2, size: Vec2) -> Entity {
+    app.world
+        .spawn()
+        .insert_bundle(ColliderBundle::wall(size.x, size.y))
+        .insert(Transform::from_translation(position.extend(10.)))
+        .insert(GlobalTransform::default())
+        .id()
+}
+
+pub fn spawn_player(app: &mut App, position: Vec2) -> Entity {
+
+    let player = app.world.resource_scope(|world, mut texture_atlases: Mut<Assets<TextureAtlas>>| {
+        let assets = world.get_resource::<AssetServer>().expect("Headless app is missing AssetServer");
+        PlayerBundle::new(Transform::from_translation(position.extend(2.)), assets, &mut texture_atlases)
+    });
+
+    app.world
+        .spawn()
+        .insert_bundle(player)
+        .id()
+}
+
+//Spawns an enemy from assets/enemies/<name>.enemy.ron the same way enemies from the LDtk level
+//are spawned, inside a level with the given uid. It's built on the next frame
+pub fn spawn_enemy(app: &mut App, name: &str, position: Vec2, level_uid: i32, respawn: Option<EnemyRespawn>) -> Entity {
+
+    let path = format!("assets/enemies/{}.enemy.ron", name);
+    let definition: EnemyDefinition = load_ron(&path).expect("Enemy definition failed to load");
+    let definition = app.world
+        .get_resource_mut::<Assets<EnemyDefinition>>()
+        .expect("Headless app is missing enemy definitions")
+        .add(definition);
+
+    //Defeated enemies are respawned into the level they came from
+    let level = app.world
+        .spawn()
+        .insert(Transform::default())
+        .insert(GlobalTransform::default())
+        .id();
+
+    let enemy = app.world
+        .spawn()
+        .insert(EnemySpawner {
+            definition,
+            level_uid,
+            respawn,
+        })
+        .insert(EntityIid {
+            level_uid,
+            iid: format!("{}-{}", name, level.id()),
+        })
+        .insert(Transform::from_translation(position.extend(2.)))
+        .insert(GlobalTransform::default())
+        .id();
+
+    app.world.entity_mut(level).push_children(&[enemy]);
+    enemy
+}
+
+//Loads an LDtk project without spawning any of its levels, for the systems that read it directly
+pub fn load_world(app: &mut App, path: &str) -> Handle<LdtkAsset> {
+    let handle: Handle<LdtkAsset> = app.world
+        .get_resource::<AssetServer>()
+        .expect("Headless app is missing AssetServer")
+        .load(path);
+    app.world.spawn().insert(handle.clone());
+
+    //Loading happens on another thread
+    for _ in 0..600 {
+        app.update();
+        let loaded = app.world
+            .get_resource::<Assets<LdtkAsset>>()
+            .expect("Headless app is missing LDtk projects")
+            .contains(&handle);
+        if loaded {
+            return handle;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    panic!("LDtk project {} failed to load", path);
+}
+
+//===============================================================
@@ -0,0 +1,39 @@
+//===============================================================
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::RegisterLdtkObjects;
+
+use crate::general::GameState;
+
+pub mod transition_components;
+mod transition_systems;
+
+//===============================================================
+
+pub struct TransitionPlugin;
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(transition_components::TransitionState::default())
+
+            .register_ldtk_entity::<transition_components::TransitionBundle>("Transition")
+            .register_ldtk_entity::<transition_components::TransitionBundle>("Door")
+
+            .add_system_to_stage(CoreStage::PreUpdate, transition_systems::freeze_input_during_transition
+                .after("UpdateActionInput")
+                .after("RecordInput")
+                .after("ReplayInput")
+                .before("FindInteractionTarget")
+            )
+
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    //A transition that just finished stays disarmed for a frame, even if the player isn't in an edge
+                    .with_system(transition_systems::trigger_transitions.label("TriggerTransition"))
+                    .with_system(transition_systems::run_transition.after("TriggerTransition"))
+            )
+        ;
+    }
+}
+
+//===============================================================
@@ -0,0 +1,148 @@
+//===============================================================
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{
+    LayerInstance, EntityInstance, LdtkAsset,
+    prelude::{TilesetDefinition, LdtkEntity},
+    utils::{ldtk_pixel_coords_to_translation, ldtk_pixel_coords_to_translation_pivoted},
+};
+
+use crate::{
+    general::tools::get_string_field,
+    interaction::interaction_components::Interactable,
+};
+
+//===============================================================
+
+pub const TRANSITION_FADE_TIME: f32 = 0.3;
+pub const DOOR_INTERACT_DISTANCE: f32 = 30.;
+
+//===============================================================
+
+//Moves the player to an EntryPoint, which can be in any level.
+//Edge transitions fire when the player walks into them, doors need the interact action
+#[derive(Component, Default, Clone, Debug)]
+pub struct Transition {
+    pub target_level: String,
+    pub entry_point: String,
+    pub size: Vec2,
+    pub is_door: bool,
+}
+
+#[derive(Bundle, Default, Clone)]
+pub struct TransitionBundle {
+    transition: Transition,
+    interactable: Interactable,
+    transform: Transform,
+    global_transform: GlobalTransform,
+}
+impl LdtkEntity for TransitionBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+
+        let is_door = entity_instance.identifier == "Door";
+
+        TransitionBundle {
+            transition: Transition {
+                target_level: get_string_field(entity_instance, "TargetLevel").unwrap_or_default(),
+                entry_point: get_string_field(entity_instance, "EntryPoint").unwrap_or_default(),
+                size: Vec2::new(entity_instance.width as f32, entity_instance.height as f32),
+                is_door,
+            },
+            interactable: Interactable {
+                prompt: "Enter".to_string(),
+                range: DOOR_INTERACT_DISTANCE,
+                enabled: is_door,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+//===============================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionTarget {
+    pub level_uid: i32,
+    pub position: Vec2,
+}
+impl TransitionTarget {
+    //Finds an EntryPoint straight from the LDtk data so the target level doesn't need to be loaded.
+    //The level can be given by identifier or uid
+    pub fn find(ldtk_asset: &LdtkAsset, target_level: &str, entry_point: &str) -> Option<Self> {
+
+        let level = ldtk_asset.project.levels.iter().find(|level| {
+            level.identifier == target_level || level.uid.to_string() == target_level
+        })?;
+
+        //Same as the level translation bevy_ecs_ldtk uses with use_level_world_translations
+        let level_origin = ldtk_pixel_coords_to_translation(
+            IVec2::new(level.world_x, level.world_y + level.px_hei),
+            ldtk_asset.world_height(),
+        );
+
+        for layer in level.layer_instances.iter().flatten() {
+            for entity_instance in layer.entity_instances.iter() {
+
+                if entity_instance.identifier != "EntryPoint" {
+                    continue;
+                }
+                if get_string_field(entity_instance, "Id").as_deref() != Some(entry_point) {
+                    continue;
+                }
+
+                let local_position = ldtk_pixel_coords_to_translation_pivoted(
+                    entity_instance.px,
+                    level.px_hei,
+                    IVec2::new(entity_instance.width, entity_instance.height),
+                    entity_instance.pivot,
+                );
+
+                return Some(TransitionTarget {
+                    level_uid: level.uid,
+                    position: level_origin + local_position,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+pub enum TransitionState {
+    Idle {
+        //Edge transitions only fire again once the player has stepped out of them
+        armed: bool,
+    },
+    FadingOut {
+        timer: Timer,
+        target: TransitionTarget,
+    },
+    FadingIn {
+        timer: Timer,
+    },
+}
+impl Default for TransitionState {
+    fn default() -> Self {
+        TransitionState::Idle {
+            armed: true,
+        }
+    }
+}
+impl TransitionState {
+    pub fn is_active(&self) -> bool {
+        !matches!(self, TransitionState::Idle { .. })
+    }
+}
+
+//The black sprite covering the screen during a transition
+#[derive(Component)]
+pub struct TransitionFade;
+
+//===============================================================
@@ -0,0 +1,193 @@
+//===============================================================
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use heron::Velocity;
+
+use super::transition_components::*;
+
+use crate::{
+    general::general_components::{FadeInOut, GameCamera, FrameDelta},
+    player::player_components::Player,
+    controls::controls_components::ActionInput,
+    interaction::interaction_components::InteractEvent,
+    world::{LevelChangedEvent, SnapCamera},
+    settings::settings_components::Settings,
+};
+
+//===============================================================
+
+pub fn trigger_transitions(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    transition_query: Query<(Entity, &GlobalTransform, &Transition)>,
+    mut interact_event: EventReader<InteractEvent>,
+    mut transition_state: ResMut<TransitionState>,
+    world_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    camera_query: Query<Entity, With<GameCamera>>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+) {
+    let armed = match *transition_state {
+        TransitionState::Idle { armed } => armed,
+        _ => return,
+    };
+
+    let player_pos = match player_query.get_single() {
+        Ok(transform) => transform.translation.truncate(),
+        Err(_) => return,
+    };
+
+    //Doors are used through the interaction system
+    let mut triggered = interact_event
+        .iter()
+        .find_map(|interaction| transition_query.get(interaction.0).ok())
+        .map(|(_, _, transition)| transition.clone());
+
+    let mut inside_edge = false;
+    for (_, transform, transition) in transition_query.iter() {
+        if transition.is_door {
+            continue;
+        }
+
+        let offset = (player_pos - transform.translation.truncate()).abs();
+        if offset.x < transition.size.x / 2. && offset.y < transition.size.y / 2. {
+            inside_edge = true;
+            if armed && triggered.is_none() {
+                triggered = Some(transition.clone());
+            }
+        }
+    }
+
+    let transition = match triggered {
+        Some(transition) => transition,
+        None => {
+            if !inside_edge && !armed {
+                *transition_state = TransitionState::Idle { armed: true };
+            }
+            return
+        },
+    };
+
+    let ldtk_asset = match world_query.get_single().ok().and_then(|handle| ldtk_assets.get(handle)) {
+        Some(ldtk_asset) => ldtk_asset,
+        None => return,
+    };
+
+    let target = match TransitionTarget::find(ldtk_asset, &transition.target_level, &transition.entry_point) {
+        Some(target) => target,
+        None => {
+            println!("Transition target not found. Level: {}, Entry point: {}", transition.target_level, transition.entry_point);
+            return
+        },
+    };
+
+    //Cover the screen before moving anything
+    if let Ok(camera) = camera_query.get_single() {
+        let camera_size = settings.camera_size();
+        let fade = commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0., 0., 0., 0.),
+                custom_size: Some(camera_size),
+                ..Default::default()
+            },
+            //The camera's transform is the bottom left of the screen
+            transform: Transform::from_xyz(camera_size.x / 2., camera_size.y / 2., -1.),
+            ..Default::default()
+        })
+        .insert(TransitionFade)
+        .insert(FadeInOut {
+            timer: Timer::from_seconds(TRANSITION_FADE_TIME, false),
+            from: 0.,
+            to: 1.,
+            remove_on_finish: false,
+            remove_component_on_finish: true,
+        })
+        .id();
+
+        commands.entity(camera).add_child(fade);
+    }
+
+    *transition_state = TransitionState::FadingOut {
+        timer: Timer::from_seconds(TRANSITION_FADE_TIME, false),
+        target,
+    };
+}
+
+pub fn run_transition(
+    mut transition_state: ResMut<TransitionState>,
+    mut player_query: Query<(&mut Transform, Option<&mut Velocity>), With<Player>>,
+    fade_query: Query<Entity, With<TransitionFade>>,
+    mut current_level: ResMut<LevelSelection>,
+    mut level_changed_event: EventWriter<LevelChangedEvent>,
+    mut snap_camera: ResMut<SnapCamera>,
+    mut commands: Commands,
+    time: Res<FrameDelta>,
+) {
+    let next_state = match &mut *transition_state {
+        TransitionState::Idle { .. } => return,
+
+        TransitionState::FadingOut { timer, target } => {
+            timer.tick(time.delta());
+            if !timer.finished() {
+                return;
+            }
+
+            //Screen is black, move everything
+            for (mut transform, velocity) in player_query.iter_mut() {
+                transform.translation.x = target.position.x;
+                transform.translation.y = target.position.y;
+                if let Some(mut velocity) = velocity {
+                    velocity.linear = Vec3::ZERO;
+                }
+            }
+
+            if *current_level != LevelSelection::Uid(target.level_uid) {
+                *current_level = LevelSelection::Uid(target.level_uid);
+                level_changed_event.send(LevelChangedEvent(target.level_uid));
+            }
+            snap_camera.0 = true;
+
+            for fade in fade_query.iter() {
+                commands.entity(fade).insert(FadeInOut {
+                    timer: Timer::from_seconds(TRANSITION_FADE_TIME, false),
+                    from: 1.,
+                    to: 0.,
+                    remove_on_finish: true,
+                    remove_component_on_finish: false,
+                });
+            }
+
+            TransitionState::FadingIn {
+                timer: Timer::from_seconds(TRANSITION_FADE_TIME, false),
+            }
+        },
+
+        TransitionState::FadingIn { timer } => {
+            timer.tick(time.delta());
+            if !timer.finished() {
+                return;
+            }
+
+            TransitionState::Idle {
+                armed: false,
+            }
+        },
+    };
+
+    *transition_state = next_state;
+}
+
+//Runs after input is read so nothing the player presses mid transition gets through
+pub fn freeze_input_during_transition(
+    transition_state: Res<TransitionState>,
+    mut action_input: ResMut<ActionInput>,
+) {
+    if transition_state.is_active() {
+        action_input.update(HashSet::new());
+    }
+}
+
+//===============================================================
@@ -2,6 +2,8 @@
 
 use bevy::prelude::*;
 
+use crate::general::GameState;
+
 pub mod ui_components;
 mod ui_systems;
 
@@ -21,6 +23,7 @@ pub enum Popups {
     Knives,
     Boots,
     Coin,
+    Weapon(String),
     End,
 }
 
@@ -40,7 +43,8 @@ impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app
 
-            .add_state(ShowPopup::Show)
+            //The intro popup is shown when a new game is started
+            .add_state(ShowPopup::Hide)
 
             .add_startup_system(ui_systems::ui_setup)
 
@@ -71,6 +75,32 @@ impl Plugin for UiPlugin {
                 SystemSet::on_update(ShowCoinCounter::Show)
                 .with_system(ui_systems::spawn_coin_popup)
             )
+
+
+            .add_system_to_stage(CoreStage::PreUpdate, ui_systems::freeze_input_while_paused
+                .after("UpdateActionInput")
+                .after("RecordInput")
+                .after("ReplayInput")
+                .before("FindInteractionTarget")
+            )
+            .add_system(ui_systems::toggle_pause)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(ui_systems::show_boss_health)
+                    .with_system(ui_systems::show_interaction_prompt)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(ui_systems::show_pause_menu)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Ending)
+                    .with_system(ui_systems::show_ending_menu)
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Dead)
+                    .with_system(ui_systems::show_game_over_menu)
+            )
         ;
     }
 }
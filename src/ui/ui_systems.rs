@@ -1,13 +1,17 @@
 //===============================================================
 
-use bevy::prelude::*;
+use bevy::{prelude::*, app::AppExit};
 use bevy_egui::{EguiContext, egui::{self, Align2}};
 
 use super::{ui_components::*, Popups, PopupExpire, ShowPopup, CoinsCollected};
 
 use crate::{
-    general::general_components::Health,
-    player::player_components::Player, world::ItemPickedUpEvent
+    general::{GameState, general_components::Health},
+    player::player_components::{Player, PlayerDeathTimer}, world::RetryEvent,
+    controls::controls_components::{ActionInput, ActionMap, PlayerAction},
+    settings::settings_components::SettingsOpen,
+    boss::boss_components::{Boss, BossFight},
+    interaction::interaction_components::{Interactable, InteractionTarget},
 };
 
 //===============================================================
@@ -27,7 +31,7 @@ pub fn show_player_health (
     assets: Res<AssetServer>,
     mut commands: Commands,
 ){
-    for health in player_query.iter() {
+    for _health in player_query.iter() {
 
         commands.spawn_bundle(NodeBundle {
             style: Style {
@@ -112,15 +116,19 @@ pub fn update_player_health (
 pub fn spawn_text_popup(
     mut egui_context: ResMut<EguiContext>,
     current_popup: Res<Popups>,
+    action_map: Res<ActionMap>,
 ) {
-    let label_text = match *current_popup {
+    //Tutorials show whatever the controls are currently bound to
+    let keys = |action: PlayerAction| action_map.display_bindings(action);
+
+    let label_text = match &*current_popup {
         Popups::Intro => {
-            "You see a small red gem fall from the sky into the village behind you.
+            format!("You see a small red gem fall from the sky into the village behind you.
 
             -----------------------------------------------------------------------
             
-            Use the arrow keys to move.
-            Press space to jump.
+            Use {} and {} to move.
+            Press {} to jump.
             
             -----------------------------------------------------------------------
             
@@ -129,47 +137,44 @@ pub fn spawn_text_popup(
             may encounter such as certain parts of the floor disabling your jump,
             platforms not working or needing to jump from level to level.
             I'm still working to address these.
-            Thanks again, Hope you enjoy"
+            Thanks again, Hope you enjoy", keys(PlayerAction::MoveLeft), keys(PlayerAction::MoveRight), keys(PlayerAction::Jump))
         },
         Popups::Gem => {
-            "A small red gem on the floor. It's warm to the touch
-            
-            Press Z to pick it up."
+            "A small red gem on the floor. It's warm to the touch".to_string()
         },
         Popups::Axe => {
-            "A sturdy looking climbing axe.
+            format!("A sturdy looking climbing axe.
             You could probably climb anything you wanted with this.
             
-            Press and hold X to hold onto walls. Release while moving
-            in a direction to jump
-            
-            Press Z to pick it up."
+            Press and hold {} to hold onto walls. Release while moving
+            in a direction to jump", keys(PlayerAction::Cling))
         },
         Popups::Knives => {
-            "You found a stash of knives on the floor here.
+            format!("You found a stash of knives on the floor here.
 
             There's... a lot of them.
 
             You shouln't have to worry about running out.
 
-            Press C to throw a knife
-            Press Z to pick them up."
+            Press {} to throw a knife", keys(PlayerAction::PrimaryAttack))
         },
         Popups::Boots => {
-            "A pair of nice looking boots.
+            format!("A pair of nice looking boots.
             
             What are they doing out here?
             
-            Press and hold Z to sprint
-            Press Z to pick them up."
+            Press and hold {} to sprint", keys(PlayerAction::Sprint))
         },
         Popups::Coin => {
             "A shiny coin.
             Who might have carelessly dropped something like this
             
-            Well, it's yours now.
-            
-            Press Z to pick it up"
+            Well, it's yours now.".to_string()
+        },
+        Popups::Weapon(weapon) => {
+            format!("You found a weapon on the floor. ({})
+
+            Press {} or {} to attack with it", weapon, keys(PlayerAction::PrimaryAttack), keys(PlayerAction::SecondaryAttack))
         },
         Popups::End => {
             "You've made it to the top mountain. 
@@ -179,7 +184,7 @@ pub fn spawn_text_popup(
             
             Did you find all the hidden items around the map?
             
-            See if you can find all the coins."
+            See if you can find all the coins.".to_string()
         },
     };
 
@@ -196,12 +201,14 @@ pub fn popup_removal(
     time: Res<Time>,
     mut expire: ResMut<PopupExpire>,
     key_input: Res<Input<KeyCode>>,
+    action_input: Res<ActionInput>,
     mut popup_state: ResMut<State<ShowPopup>>,
 ) {
 
     expire.0.tick(time.delta());
 
-    if key_input.get_just_pressed().len() > 0 && expire.0.finished() {
+    let any_pressed = key_input.get_just_pressed().len() > 0 || action_input.any_just_pressed();
+    if any_pressed && expire.0.finished() {
         popup_state.set(ShowPopup::Hide).unwrap();
     }
 
@@ -228,4 +235,167 @@ pub fn spawn_coin_popup (
     });
 }
 
-//===============================================================
\ No newline at end of file
+//===============================================================
+
+//Only the pause action gets through while the game isn't being played
+pub fn freeze_input_while_paused(
+    game_state: Res<State<GameState>>,
+    mut action_input: ResMut<ActionInput>,
+) {
+    if *game_state.current() != GameState::Playing {
+        action_input.retain_only(&[PlayerAction::Pause]);
+    }
+}
+
+pub fn toggle_pause(
+    action_input: Res<ActionInput>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if !action_input.just_pressed(PlayerAction::Pause) {
+        return;
+    }
+
+    let next_state = match game_state.current() {
+        GameState::Playing => GameState::Paused,
+        GameState::Paused => GameState::Playing,
+        //Back out of the map into the pause menu
+        GameState::Map => GameState::Paused,
+        _ => return,
+    };
+    //A menu button may have already changed the state this frame
+    let _ = game_state.set(next_state);
+}
+
+pub fn show_pause_menu(
+    mut egui_context: ResMut<EguiContext>,
+    mut game_state: ResMut<State<GameState>>,
+    mut settings_open: ResMut<SettingsOpen>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    let mut next_state = None;
+
+    egui::Window::new("Paused")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_context.ctx_mut(), |ui| {
+        if ui.button("Resume").clicked() {
+            next_state = Some(GameState::Playing);
+        }
+        if ui.button("Map").clicked() {
+            next_state = Some(GameState::Map);
+        }
+        if ui.button("Settings").clicked() {
+            settings_open.0 = !settings_open.0;
+        }
+        if ui.button("Quit").clicked() {
+            app_exit.send(AppExit);
+        }
+    });
+
+    if let Some(next_state) = next_state {
+        let _ = game_state.set(next_state);
+    }
+}
+
+pub fn show_ending_menu(
+    mut egui_context: ResMut<EguiContext>,
+    mut game_state: ResMut<State<GameState>>,
+    coins: Res<CoinsCollected>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    egui::Window::new("The End")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_context.ctx_mut(), |ui| {
+        ui.label(format!("Coins collected: {}", coins.0));
+        if ui.button("Keep exploring").clicked() {
+            let _ = game_state.set(GameState::Playing);
+        }
+        if ui.button("Quit").clicked() {
+            app_exit.send(AppExit);
+        }
+    });
+}
+
+//Shown while the player is close enough to use something
+pub fn show_interaction_prompt(
+    mut egui_context: ResMut<EguiContext>,
+    target: Res<InteractionTarget>,
+    interactable_query: Query<&Interactable>,
+    action_map: Res<ActionMap>,
+) {
+    let interactable = match target.0.and_then(|entity| interactable_query.get(entity).ok()) {
+        Some(interactable) => interactable,
+        None => return,
+    };
+
+    let buttons = action_map.display_bindings(PlayerAction::Interact);
+
+    egui::Window::new("Interact")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_BOTTOM, egui::Vec2::new(0., -100.))
+        .show(egui_context.ctx_mut(), |ui| {
+        ui.label(format!("[{}] {}", buttons, interactable.prompt));
+    });
+}
+
+//Shown along the top of the screen for as long as the arena is locked
+pub fn show_boss_health(
+    boss_query: Query<(&Boss, &Health)>,
+    fight: Res<Option<BossFight>>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    let fight = match &*fight {
+        Some(fight) => fight,
+        None => return,
+    };
+
+    for (boss, health) in boss_query.iter() {
+        if boss.name != fight.boss {
+            continue;
+        }
+
+        let health_percent = health.get_health() as f32 / health.get_max_health() as f32;
+
+        egui::Area::new("boss_health")
+            .anchor(Align2::CENTER_TOP, egui::Vec2::new(0., 20.))
+            .show(egui_context.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(&boss.name);
+                ui.add(egui::ProgressBar::new(health_percent.max(0.)).desired_width(400.));
+            });
+        });
+    }
+}
+
+//Shown once the death animation is over
+pub fn show_game_over_menu(
+    mut egui_context: ResMut<EguiContext>,
+    mut game_state: ResMut<State<GameState>>,
+    death_timer: Res<PlayerDeathTimer>,
+    mut retry_event: EventWriter<RetryEvent>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if !death_timer.0.finished() {
+        return;
+    }
+
+    egui::Window::new("Game Over")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_context.ctx_mut(), |ui| {
+        if ui.button("Retry from checkpoint").clicked() {
+            retry_event.send(RetryEvent);
+            let _ = game_state.set(GameState::Playing);
+        }
+        if ui.button("Quit").clicked() {
+            app_exit.send(AppExit);
+        }
+    });
+}
+
+//===============================================================
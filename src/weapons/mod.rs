@@ -2,9 +2,12 @@
 
 use bevy::prelude::*;
 
+use crate::general::GameState;
+
 pub mod weapon_components;
 mod weapon_systems;
 pub mod weapon_prefabs;
+pub mod weapon_definitions;
 
 //===============================================================
 
@@ -13,15 +16,24 @@ impl Plugin for WeaponPlugin {
     fn build(&self, app: &mut App) {
         app
 
+            .add_asset::<weapon_definitions::WeaponDefinition>()
+            .init_asset_loader::<weapon_definitions::WeaponDefinitionLoader>()
+            //Before gameplay, which can despawn a weapon on the frame it loads when its holder dies or changes weapons
+            .add_system_to_stage(CoreStage::PreUpdate, weapon_systems::build_weapons)
+            .add_system(weapon_systems::reload_weapon_definitions)
+
             .add_event::<weapon_components::FireWeaponEvent>()
 
-            .add_system(weapon_systems::charge_weapon)
-            .add_system(weapon_systems::weapon_state_change)
-            .add_system(weapon_systems::fire_weapon)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(weapon_systems::charge_weapon)
+                    .with_system(weapon_systems::weapon_state_change)
+                    .with_system(weapon_systems::fire_weapon)
 
-            .add_system(weapon_systems::projectile_expire)
+                    .with_system(weapon_systems::projectile_expire)
 
-            .add_system(weapon_systems::projectile_collision)
+                    .with_system(weapon_systems::projectile_collision)
+            )
         ;
     }
 }
@@ -10,7 +10,7 @@ use heron::{Velocity, CollisionShape, RigidBody, AxisAngle};
 use std::time::Duration;
 
 use crate::{
-    general::tools::rotate_vector,
+    general::{tools::rotate_vector, general_components::Damage},
     physics::physics_components::{ColliderBundle, SetGravityScale}, animation::animation_components::SimpleAnimationBundle
 };
 
@@ -40,27 +40,31 @@ impl WeaponInventory {
             self.slot1 = Some(entity);  
             return true
         }
-        return false
+        false
     }
     pub fn add_slot2_weapon(&mut self, entity: Entity) -> bool {
         if self.slot2.is_none() {
             self.slot2 = Some(entity);
             return true
         }
-        return false
+        false
+    }
+    //Puts the weapon in the first empty slot
+    pub fn add_weapon(&mut self, entity: Entity) -> bool {
+        self.add_slot1_weapon(entity) || self.add_slot2_weapon(entity)
     }
 
     pub fn get_slot1(&self) -> &Option<Entity> {
-        return &self.slot1
+        &self.slot1
     }
     pub fn get_slot2(&self) -> &Option<Entity> {
-        return &self.slot2
+        &self.slot2
     }
     pub fn _has_slot1(&self) -> bool {
-        return self.slot1.is_some()
+        self.slot1.is_some()
     }
     pub fn _has_slot2(&self) -> bool {
-        return self.slot2.is_some()
+        self.slot2.is_some()
     }
 }
 
@@ -77,6 +81,12 @@ pub struct WeaponBundle {
     pub attack:     WeaponAttack,
 }
 
+//----------------------------------------------
+//Weapon id, used to recreate the weapon when loading a save
+
+#[derive(Component, Clone, Default)]
+pub struct WeaponId(pub String);
+
 //----------------------------------------------
 //Weapon states, Charge and previews
 
@@ -91,10 +101,12 @@ pub struct WeaponDirection {
     pub direction: WeaponDirections,
 }
 
+#[derive(Default)]
 pub enum WeaponDirections {
     Up,
 
     ForwardUp,
+    #[default]
     Forward,
     ForwardDown,
 
@@ -104,11 +116,6 @@ pub enum WeaponDirections {
     Backward,
     BackwardUp,
 }
-impl Default for WeaponDirections {
-    fn default() -> Self {
-        WeaponDirections::Forward
-    }
-}
 impl WeaponDirections {
     pub fn from_vec2(dir: Vec2) -> Self {
         
@@ -215,7 +222,8 @@ pub struct WeaponAttack {
 }
 
 pub struct ProjectileTemplate {
-    pub damage: i32,
+    pub damage: Damage,
+    pub knockback: f32,
     pub expire: f32,
     pub size: CollisionShape,
 
@@ -227,51 +235,6 @@ pub struct ProjectileTemplate {
     pub rigid_body: RigidBody,
     pub animation_bundle: SimpleAnimationBundle,
 }
-impl ProjectileTemplate {
-    pub fn create_range(
-        damage: i32, expire: f32, spawn_offset: Vec2, 
-        width: f32, height: f32, 
-        initial_speed: Vec2, initial_spin_angle: f32,
-        rigid_body: RigidBody,
-        animation_bundle: SimpleAnimationBundle,
-
-    ) -> Self {
-
-        ProjectileTemplate {
-            damage,
-            expire,
-            size: CollisionShape::Cuboid{
-                half_extends: Vec3::new(width, height, 0.) / 2.,
-                border_radius: None,
-            },
-            initial_speed,
-            initial_spin_angle,
-            spawn_offset,
-            rigid_body,
-            animation_bundle,
-        }
-    }
-
-    pub fn create_melee(
-        damage: i32, expire: f32, spawn_offset: Vec2, 
-        width: f32, height: f32,
-        animation_bundle: SimpleAnimationBundle,
-    ) -> Self {
-        ProjectileTemplate {
-            damage,
-            expire,
-            size: CollisionShape::Cuboid {
-                half_extends: Vec3::new(width, height, 0.) / 2.,
-                border_radius: None,
-            },
-            initial_speed: Vec2::ZERO,
-            initial_spin_angle: 0.,
-            spawn_offset,
-            rigid_body: RigidBody::Dynamic,
-            animation_bundle,
-        }
-    }
-}
 
 //================================================================================
 
@@ -284,6 +247,7 @@ pub struct FireWeaponEvent(pub Entity);
 pub struct ProjectileAttackBundle {
     projectile: Projectile,
     damage: ProjectileDamage,
+    knockback: ProjectileKnockback,
     expire: ProjectileExpire,
     velocity: Velocity,
     #[bundle]
@@ -322,7 +286,8 @@ impl ProjectileAttackBundle {
 
         ProjectileAttackBundle {
             projectile: Projectile(parent),
-            damage: ProjectileDamage(base.damage),
+            damage: ProjectileDamage(base.damage.clone()),
+            knockback: ProjectileKnockback(base.knockback),
             expire: ProjectileExpire::new(base.expire),
             velocity: Velocity::from_linear(projectile_velocity.extend(0.))
                 .with_angular(axis_angle),
@@ -343,7 +308,8 @@ impl ProjectileAttackBundle {
 #[derive(Component, Clone)] 
 pub struct Projectile (pub Entity);
 
-#[derive(Component)] pub struct ProjectileDamage(pub i32);
+#[derive(Component)] pub struct ProjectileDamage(pub Damage);
+#[derive(Component)] pub struct ProjectileKnockback(pub f32);
 #[derive(Component)] pub struct ProjectileExpire(Timer);
 impl ProjectileExpire {
     pub fn new(expire_time: f32) -> Self {
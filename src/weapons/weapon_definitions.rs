@@ -0,0 +1,146 @@
+//================================================================================
+
+use bevy::{
+    prelude::*,
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use heron::{RigidBody, CollisionShape};
+use serde::Deserialize;
+
+use crate::{
+    animation::animation_components::{AnimationDefinition, SimpleAnimationBundle},
+    physics::physics_components::SetGravityScale,
+    general::general_components::{Damage, DamageType, StatusEffect},
+};
+
+use super::weapon_components::*;
+
+//================================================================================
+
+//Heron's RigidBody can't be deserialized so mirror the parts projectiles use
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum ProjectileBody {
+    Dynamic,
+    KinematicVelocityBased,
+    Sensor,
+}
+impl ProjectileBody {
+    fn to_rigid_body(self) -> RigidBody {
+        match self {
+            ProjectileBody::Dynamic                 => RigidBody::Dynamic,
+            ProjectileBody::KinematicVelocityBased  => RigidBody::KinematicVelocityBased,
+            ProjectileBody::Sensor                  => RigidBody::Sensor,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ProjectileDefinition {
+    pub damage: i32,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    #[serde(default)]
+    pub status: Option<StatusEffect>,
+    //Force the projectile pushes whatever it hits away with
+    #[serde(default)]
+    pub knockback: f32,
+    pub expire: f32,
+    pub spawn_offset: Vec2,
+    pub size: Vec2,
+
+    pub initial_speed: Vec2,
+    pub initial_spin_angle: f32,
+
+    pub rigid_body: ProjectileBody,
+    pub animation: AnimationDefinition,
+}
+
+//Everything needed to build a weapon. Loaded from assets/weapons/*.weapon.ron
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "9d6c3c61-2f7e-4b8a-bb0e-3a5f0e7d4c19"]
+pub struct WeaponDefinition {
+    pub name: String,
+    pub charge_time: f32,
+    pub child_of_parent: bool,
+    pub gravity_scale: Option<SetGravityScale>,
+    pub projectile: ProjectileDefinition,
+}
+impl WeaponDefinition {
+    pub fn asset_path(weapon_id: &str) -> String {
+        format!("weapons/{}.weapon.ron", weapon_id.to_lowercase())
+    }
+
+    pub fn create_weapon(
+        &self,
+        assets: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+        is_friendly: bool,
+    ) -> WeaponBundle {
+
+        let projectile = &self.projectile;
+
+        let atlas_handle = projectile.animation.load_texture_atlas(assets, texture_atlases);
+        let animation_bundle = SimpleAnimationBundle::new(
+            projectile.animation.animation_type.clone(),
+            projectile.animation.frame_steps.clone(),
+            projectile.animation.repeating,
+            atlas_handle,
+        );
+
+        WeaponBundle {
+            charge: WeaponCharge::new(self.charge_time),
+            attack: WeaponAttack {
+                to_spawn: ProjectileTemplate {
+                    damage: Damage {
+                        amount: projectile.damage,
+                        damage_type: projectile.damage_type,
+                        status: projectile.status,
+                    },
+                    knockback: projectile.knockback,
+                    expire: projectile.expire,
+                    size: CollisionShape::Cuboid {
+                        half_extends: projectile.size.extend(0.) / 2.,
+                        border_radius: None,
+                    },
+                    initial_speed: projectile.initial_speed,
+                    initial_spin_angle: projectile.initial_spin_angle,
+                    spawn_offset: projectile.spawn_offset,
+                    rigid_body: projectile.rigid_body.to_rigid_body(),
+                    animation_bundle,
+                },
+                child_of_parent: self.child_of_parent,
+                is_friendly,
+                gravity_scale: self.gravity_scale.clone(),
+            },
+            state: WeaponState::default(),
+            direction: WeaponDirection::default(),
+            preview: WeaponPreviewBundle::default(),
+        }
+    }
+}
+
+//================================================================================
+
+#[derive(Default)]
+pub struct WeaponDefinitionLoader;
+impl AssetLoader for WeaponDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let definition: WeaponDefinition = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(definition));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["weapon.ron"]
+    }
+}
+
+//================================================================================
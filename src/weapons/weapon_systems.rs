@@ -2,15 +2,56 @@
 
 use bevy::prelude::*;
 use heron::{Velocity, RigidBody, CollisionEvent, CollisionLayers};
-use crate::{physics::physics_components::{CollisionLayer}, general::general_components::{HealthChangeEvent, HealthChangeType}};
+use crate::{physics::physics_components::{CollisionLayer}, general::general_components::{HealthChangeEvent, HealthChangeType, Knockback, FrameDelta}};
 
-use super::weapon_components::*;
+use super::{
+    weapon_components::*,
+    weapon_definitions::WeaponDefinition,
+    weapon_prefabs::{WeaponSpawner, WeaponBuilt},
+};
+
+//================================================================================
+
+pub fn build_weapons(
+    spawner_query: Query<(Entity, &WeaponSpawner), Without<WeaponBuilt>>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    assets: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut commands: Commands,
+) {
+    for (entity, spawner) in spawner_query.iter() {
+        if let Some(definition) = definitions.get(&spawner.definition) {
+
+            commands.entity(entity)
+                .insert_bundle(definition.create_weapon(&assets, &mut texture_atlases, spawner.is_friendly))
+                .insert(Name::new(definition.name.clone()))
+                .insert(WeaponBuilt);
+        }
+    }
+}
+
+//Rebuild weapons when their definition file is changed
+pub fn reload_weapon_definitions(
+    mut definition_events: EventReader<AssetEvent<WeaponDefinition>>,
+    spawner_query: Query<(Entity, &WeaponSpawner), With<WeaponBuilt>>,
+    mut commands: Commands,
+) {
+    for event in definition_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            for (entity, spawner) in spawner_query.iter() {
+                if spawner.definition == *handle {
+                    commands.entity(entity).remove::<WeaponBuilt>();
+                }
+            }
+        }
+    }
+}
 
 //================================================================================
 
 pub fn charge_weapon(
     mut query: Query<(&WeaponState, &mut WeaponCharge)>,
-    time: Res<Time>,
+    time: Res<FrameDelta>,
 ) {
     for (state, mut charge) in query.iter_mut() {
         if state.charging {
@@ -45,7 +86,7 @@ pub fn fire_weapon(
 
         if let Ok((entity, transform, mut charge, attack, direction)) = weapon_query.get_mut(event.0) {
 
-            let mut new_projectile = ProjectileAttackBundle::new(entity, &attack.to_spawn, &direction, attack.is_friendly);
+            let mut new_projectile = ProjectileAttackBundle::new(entity, &attack.to_spawn, direction, attack.is_friendly);
 
             if !attack.child_of_parent {
                 new_projectile.add_transform(transform.translation);
@@ -74,7 +115,7 @@ pub fn fire_weapon(
 
 pub fn projectile_expire(
     mut projectile_query: Query<(&mut ProjectileExpire, Entity)>,
-    time: Res<Time>,
+    time: Res<FrameDelta>,
     mut commands: Commands,
 ) {
 
@@ -90,89 +131,89 @@ pub fn projectile_expire(
 //================================================================================
 
 pub fn projectile_collision(
-    mut projectile_query: Query<(Entity, &mut CollisionLayers, Option<&ProjectileDamage>, Option<&mut Velocity>), With<Projectile>>,
+    mut projectile_query: Query<(
+        Entity, &mut CollisionLayers, Option<&ProjectileDamage>, Option<&mut Velocity>,
+        &GlobalTransform, Option<&ProjectileKnockback>,
+    ), With<Projectile>>,
     mut collision_events: EventReader<CollisionEvent>,
     mut health_events: EventWriter<HealthChangeEvent>,
     mut commands: Commands,
 ) {
 
     for event in collision_events.iter() {
-        match event {
-            CollisionEvent::Started(d1, d2) => {
-
-                let d1_weapon = if d1.collision_layers().contains_group(CollisionLayer::Weapon) {
-                    true
-                } else {
-                    false
-                };
-                let d2_weapon = if d2.collision_layers().contains_group(CollisionLayer::Weapon) {
-                    true
-                } else {
-                    false
-                };
-
-                //This should never happen but since we're
-                //comparing both, might as well check
-                if d1_weapon && d2_weapon { 
-                    continue;
-                }
-                //Neither of the collisions were weapons
-                if !d1_weapon && !d2_weapon {
-                    continue;
-                }
+        if let CollisionEvent::Started(d1, d2) = event {
 
-                let (weapon, to_test) = if d1_weapon {
-                    (d1, d2)
-                } else {
-                    (d2, d1)
-                };
-
-
-                //Collided with a wall. If ranged projectile, should be
-                //disabled
-                if to_test.collision_layers().contains_group(CollisionLayer::Tile) {
-                    if let Ok((entity, mut layer, _, _)) = projectile_query.get_mut(weapon.rigid_body_entity()) {
-                        //If collided, remove projectile damage and disable physics
-                        commands.entity(entity)
-                            .remove::<ProjectileDamage>()
-                            //.insert(SetGravityScale(0.));
-                            //.insert(RigidBody::Sensor);
-                            .remove::<RigidBody>();
-                        
-                        *layer = CollisionLayers::none();
-                        
-                        //layer
-                            //.without_mask(CollisionLayer::Tile)
-                            //.without_mask(CollisionLayer::Entity)
-                            //.without_mask(CollisionLayer::Player);
-                    }
+            let d1_weapon = d1.collision_layers().contains_group(CollisionLayer::Weapon);
+            let d2_weapon = d2.collision_layers().contains_group(CollisionLayer::Weapon);
+
+            //This should never happen but since we're
+            //comparing both, might as well check
+            if d1_weapon && d2_weapon { 
+                continue;
+            }
+            //Neither of the collisions were weapons
+            if !d1_weapon && !d2_weapon {
+                continue;
+            }
+
+            let (weapon, to_test) = if d1_weapon {
+                (d1, d2)
+            } else {
+                (d2, d1)
+            };
+
+
+            //Collided with a wall. If ranged projectile, should be
+            //disabled
+            if to_test.collision_layers().contains_group(CollisionLayer::Tile) {
+                if let Ok((entity, mut layer, _, _, _, _)) = projectile_query.get_mut(weapon.rigid_body_entity()) {
+                    //If collided, remove projectile damage and disable physics
+                    commands.entity(entity)
+                        .remove::<ProjectileDamage>()
+                        //.insert(SetGravityScale(0.));
+                        //.insert(RigidBody::Sensor);
+                        .remove::<RigidBody>();
+                    
+                    *layer = CollisionLayers::none();
+                    
+                    //layer
+                        //.without_mask(CollisionLayer::Tile)
+                        //.without_mask(CollisionLayer::Entity)
+                        //.without_mask(CollisionLayer::Player);
                 }
-                //Collided with an entity. Because weapon should not collide
-                //with entity layer directly, it will have collided with
-                //friendly or enemy layer and hence this so dont have to check
-                //for friendly fire (thats what i'm hoping for at least)
-                else if to_test.collision_layers().contains_group(CollisionLayer::Entity) {
-                    if let Ok((_, mut layer, Some(damage), Some(mut velocity))) = projectile_query.get_mut(weapon.rigid_body_entity()) {
-
-                        velocity.linear *= 0.2;
-
-                        let to_damage = to_test.rigid_body_entity();
-                        health_events.send(
-                            HealthChangeEvent {
-                                entity: to_damage,
-                                change_type: HealthChangeType::Add{value: -damage.0},
-                            }
-                        );
-
-                        *layer = layer
-                            .without_mask(CollisionLayer::Entity)
-                            .without_mask(CollisionLayer::Player)
-                            .without_mask(CollisionLayer::Enemy);
-                            //.without_group(CollisionLayer::Weapon);
-                    }
+            }
+            //Collided with an entity. Because weapon should not collide
+            //with entity layer directly, it will have collided with
+            //friendly or enemy layer and hence this so dont have to check
+            //for friendly fire (thats what i'm hoping for at least)
+            else if to_test.collision_layers().contains_group(CollisionLayer::Entity) {
+                if let Ok((_, mut layer, Some(damage), Some(mut velocity), transform, knockback)) = projectile_query.get_mut(weapon.rigid_body_entity()) {
+
+                    velocity.linear *= 0.2;
+
+                    let knockback = knockback
+                        .filter(|knockback| knockback.0 > 0.)
+                        .map(|knockback| Knockback {
+                            source: transform.translation.truncate(),
+                            force: knockback.0,
+                        });
+
+                    let to_damage = to_test.rigid_body_entity();
+                    health_events.send(
+                        HealthChangeEvent {
+                            entity: to_damage,
+                            change_type: HealthChangeType::Damage(damage.0.clone()),
+                            knockback,
+                        }
+                    );
+
+                    *layer = layer
+                        .without_mask(CollisionLayer::Entity)
+                        .without_mask(CollisionLayer::Player)
+                        .without_mask(CollisionLayer::Enemy);
+                        //.without_group(CollisionLayer::Weapon);
                 }
-            },
-            _ => {}
+            }
         }
     }
 }
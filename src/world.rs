@@ -5,10 +5,16 @@ use std::collections::{HashMap, HashSet};
 use bevy::{prelude::*, render::camera::ScalingMode};
 use heron::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
+use serde::{Serialize, Deserialize};
 
 use crate::{
-    player::player_components::{Player, PLAYER_PICKUP_DISTANCE, PLAYER_INTERACT, PlayerSprint, PlayerWallCling}, 
-    general::general_components::{FadeInOut, GameCamera}, physics::physics_components::CollisionLayer, weapons::weapon_components::{WeaponInventory, WeaponBundle}, ui::{Popups, ShowPopup, ShowCoinCounter, CoinsCollected}
+    player::player_components::{Player, PLAYER_PICKUP_DISTANCE, PlayerSprint, PlayerWallCling}, 
+    general::general_components::{FadeInOut, GameCamera, EntityIid, Health, HealthFlash, StatusEffects, HitStun, HealthChangeEvent}, physics::physics_components::{ColliderBundle, MaxVelocity}, weapons::{weapon_components::{WeaponInventory, Projectile}, weapon_prefabs::{WeaponSpawnerBundle, THROWING_KNIFE_ID}}, ui::{Popups, ShowPopup, ShowCoinCounter, CoinsCollected},
+    interaction::interaction_components::{Interactable, InteractEvent, InteractionTarget},
+    transition::transition_components::TransitionState,
+    general::GameState,
+    settings::settings_components::Settings,
+    animation::animation_components::{AutoAnimation, AnimationType, ChangeAnimationEvent, FlipAnimation},
 };
 
 //============================================================================
@@ -29,16 +35,40 @@ pub struct LevelChangedEvent(pub i32);
 
 //============================================================================
 
-//============================================================================
+//The wall tiles of a level, kept around after the colliders are built so other things
+//(like the minimap and navigation) can use them. GridCoords start at the bottom left of the level
+#[derive(Default, Clone, Debug)]
+pub struct LevelWallGrid {
+    //Bottom left of the level in the world
+    pub origin: Vec2,
+    pub width: i32,
+    pub height: i32,
+    pub grid_size: i32,
+    pub walls: HashSet<GridCoords>,
+}
+impl LevelWallGrid {
+    pub fn in_bounds(&self, coords: GridCoords) -> bool {
+        coords.x >= 0 && coords.y >= 0 && coords.x < self.width && coords.y < self.height
+    }
+    pub fn is_wall(&self, coords: GridCoords) -> bool {
+        self.walls.contains(&coords)
+    }
+}
 
+//Level uid -> wall grid, for every level that has been loaded
+#[derive(Default, Clone, Debug)]
+pub struct LevelWallGrids(pub HashMap<i32, LevelWallGrid>);
+
+//============================================================================
 
 //Algorithm taken from the bevy_ecs_ldtk github in their platformer example
 fn spawn_wall_collision(
     mut commands: Commands,
     wall_query: Query<(&GridCoords, &Parent), Added<Wall>>,
     parent_query: Query<&Parent, Without<Wall>>,
-    level_query: Query<(Entity, &Handle<LdtkLevel>)>,
+    level_query: Query<(Entity, &Handle<LdtkLevel>, &Transform)>,
     levels: Res<Assets<LdtkLevel>>,
+    mut wall_grids: ResMut<LevelWallGrids>,
 ) {
 
     /// Represents a wide wall that is 1 tile tall
@@ -59,13 +89,13 @@ fn spawn_wall_collision(
         if let Ok(&Parent(level_entity)) = parent_query.get(parent) {
             level_to_wall_locations
                 .entry(level_entity)
-                .or_insert(HashSet::new())
+                .or_default()
                 .insert(grid_coords);
         }
     });
 
     if !wall_query.is_empty() {
-        level_query.for_each(|(level_entity, level_handle)| {
+        level_query.for_each(|(level_entity, level_handle, level_transform)| {
             if let Some(level_walls) = level_to_wall_locations.get(&level_entity) {
                 let level = levels
                     .get(level_handle)
@@ -82,6 +112,15 @@ fn spawn_wall_collision(
                     .clone()
                     .expect("Level asset should have layers")[0];
 
+                let wall_grid = wall_grids.0
+                    .entry(level.level.uid)
+                    .or_default();
+                wall_grid.origin = level_transform.translation.truncate();
+                wall_grid.width = width;
+                wall_grid.height = height;
+                wall_grid.grid_size = grid_size;
+                wall_grid.walls.extend(level_walls.iter().copied());
+
                 // combine wall tiles into flat "plates" in each individual row
                 let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
 
@@ -119,7 +158,7 @@ fn spawn_wall_collision(
                 for (y, row) in plate_stack.iter().enumerate() {
                     let mut current_rects: HashMap<Plate, Rect<i32>> = HashMap::new();
                     for plate in row {
-                        if let Some(previous_rect) = previous_rects.remove(&plate) {
+                        if let Some(previous_rect) = previous_rects.remove(plate) {
                             current_rects.insert(
                                 *plate,
                                 Rect {
@@ -145,31 +184,14 @@ fn spawn_wall_collision(
                     previous_rects = current_rects;
                 }
 
-                let collision_layer = CollisionLayers::all_masks::<CollisionLayer>()
-                    .without_mask(CollisionLayer::Tile)
-                    .with_group(CollisionLayer::Tile);
-
                 // spawn colliders for every rectangle
                 for wall_rect in wall_rects {
                     commands
                         .spawn()
-                        .insert(CollisionShape::Cuboid {
-                            half_extends: Vec3::new(
-                                (wall_rect.right as f32 - wall_rect.left as f32 + 1.)
-                                    * grid_size as f32
-                                    / 2.,
-                                (wall_rect.top as f32 - wall_rect.bottom as f32 + 1.)
-                                    * grid_size as f32
-                                    / 2.,
-                                0.,
-                            ),
-                            border_radius: None,
-                        })
-                        .insert(RigidBody::Static)
-                        .insert(PhysicMaterial {
-                            friction: 0.,
-                            ..Default::default()
-                        })
+                        .insert_bundle(ColliderBundle::wall(
+                            (wall_rect.right as f32 - wall_rect.left as f32 + 1.) * grid_size as f32,
+                            (wall_rect.top as f32 - wall_rect.bottom as f32 + 1.) * grid_size as f32,
+                        ))
                         .insert(Transform::from_xyz(
                             (wall_rect.left + wall_rect.right + 1) as f32 * grid_size as f32 / 2.,
                             (wall_rect.bottom + wall_rect.top + 1) as f32 * grid_size as f32 / 2.,
@@ -179,8 +201,7 @@ fn spawn_wall_collision(
                         // Making the collider a child of the level serves two purposes:
                         // 1. Adjusts the transforms to be relative to the level for free
                         // 2. the colliders will be despawned automatically when levels unload
-                        .insert(Parent(level_entity))
-                        .insert(collision_layer.clone());
+                        .insert(Parent(level_entity));
                 }
             }
         });
@@ -196,7 +217,12 @@ fn change_level(
 
     mut player_query: Query<&Transform, With<Player>>,
     mut level_changed_event: EventWriter<LevelChangedEvent>,
+    transition_state: Res<TransitionState>,
 ) {
+    //Transitions pick the level themselves
+    if transition_state.is_active() {
+        return;
+    }
 
     //Iterate over each of the levels in the world
     for (level_handle, level_transform) in level_query.iter() {
@@ -216,13 +242,11 @@ fn change_level(
                     && player_transform.translation.x > level_bounds.left
                     && player_transform.translation.y < level_bounds.top
                     && player_transform.translation.y > level_bounds.bottom
+                    && !current_level.is_match(&0, &ldtk_level.level)
                 {
-
-                    if !current_level.is_match(&0, &ldtk_level.level) {
-                        *current_level = LevelSelection::Uid(ldtk_level.level.uid);
-                        level_changed_event.send(LevelChangedEvent(ldtk_level.level.uid));
-                        return
-                    }
+                    *current_level = LevelSelection::Uid(ldtk_level.level.uid);
+                    level_changed_event.send(LevelChangedEvent(ldtk_level.level.uid));
+                    return
                 }
             }
         }
@@ -237,13 +261,15 @@ fn set_fog_of_war(
     mut level_changed_event: EventReader<LevelChangedEvent>,
     mut commands: Commands,
     fog_query: Query<Entity, With<FogOfWar>>,
+    settings: Res<Settings>,
 ) {
+    let camera_size = settings.camera_size();
     for _event in level_changed_event.iter() {
         
         for (level_handle, level_transform) in level_query.iter() {
             if let Some(ldtk_level) = ldtk_levels.get(level_handle) {
                 let level = &ldtk_level.level;
-                if current_level.is_match(&0, &level) {
+                if current_level.is_match(&0, level) {
 
                     for entity in fog_query.iter() {
                         commands.entity(entity).insert(FadeInOut {
@@ -259,9 +285,9 @@ fn set_fog_of_war(
                     let level_width = level.px_wid as f32;
                     let level_height = level.px_hei as f32;
 
-                    let vertical_fog_height = MAX_CAMERA_HEIGHT / 2.;
+                    let vertical_fog_height = camera_size.y / 2.;
 
-                    let horizontal_fog_width = MAX_CAMERA_WIDTH / 2.;
+                    let horizontal_fog_width = camera_size.x / 2.;
 
 
                     //=========================================================================
@@ -368,13 +394,8 @@ fn set_fog_of_war(
 //============================================================================
 
 
-const ASPECT_RATIO_WIDTH: f32 = 16.;
-const ASPECT_RATIO_HEIGHT: f32 = 10.;
-
-//const ASPECT_RATIO: f32 = ASPECT_RATIO_WIDTH / ASPECT_RATIO_HEIGHT;
-
-const MAX_CAMERA_WIDTH: f32 = ASPECT_RATIO_WIDTH    * 35.;
-const MAX_CAMERA_HEIGHT: f32 = ASPECT_RATIO_HEIGHT  * 35.;
+//Set to jump the camera straight to the player instead of panning, e.g. after a room transition
+pub struct SnapCamera(pub bool);
 
 
 fn camera_follow_player(
@@ -383,7 +404,10 @@ fn camera_follow_player(
     level_query: Query<(&Transform, &Handle<LdtkLevel>), (Without<OrthographicProjection>, Without<Player>)>,
     current_level: Res<LevelSelection>,
     ldtk_levels: Res<Assets<LdtkLevel>>,
+    mut snap_camera: ResMut<SnapCamera>,
+    settings: Res<Settings>,
 ) {
+    let camera_size = settings.camera_size();
 
     if let Ok(player_transform) = player_query.get_single() {
         let player_pos = player_transform.translation;
@@ -393,40 +417,47 @@ fn camera_follow_player(
         for (level_transform, level_handle) in level_query.iter() {
             if let Some(ldtk_level) = ldtk_levels.get(level_handle) {
                 let level = &ldtk_level.level;
-                if current_level.is_match(&0, &level) {
+                if current_level.is_match(&0, level) {
 
 
                     //Reset the camera positions. These probably wont ever change for the time being
                     camera_projection.scaling_mode = ScalingMode::None;
                     camera_projection.left = 0.;
                     camera_projection.bottom = 0.;
-                    camera_projection.right = MAX_CAMERA_WIDTH;
-                    camera_projection.top = MAX_CAMERA_HEIGHT;
+                    camera_projection.right = camera_size.x;
+                    camera_projection.top = camera_size.y;
                     //With the OrthographicProjection left, right, top bottom in this setup, the 
                     //camera_transform.translation will be in the bottom left of what you can see.
 
                     let mut camera_target = Vec2::ZERO;
 
                     let level_height = level.px_hei as f32;
-                    if level_height < MAX_CAMERA_HEIGHT {   //There is less level than there is camera vertically
-                        camera_target.y = level_transform.translation.y + (level_height / 2.) - MAX_CAMERA_HEIGHT / 2.;
+                    if level_height < camera_size.y {   //There is less level than there is camera vertically
+                        camera_target.y = level_transform.translation.y + (level_height / 2.) - camera_size.y / 2.;
                     }
                     else {  //There is more level than there is camera vertically
                         let level_bottom = level_transform.translation.y;
-                        let level_top = level_bottom + level.px_hei as f32 - MAX_CAMERA_HEIGHT;
+                        let level_top = level_bottom + level.px_hei as f32 - camera_size.y;
                         
-                        camera_target.y = (player_pos.y - MAX_CAMERA_HEIGHT / 2.).clamp(level_bottom, level_top);
+                        camera_target.y = (player_pos.y - camera_size.y / 2.).clamp(level_bottom, level_top);
                     }
                     
                     let level_width = level.px_wid as f32;
-                    if level_width < MAX_CAMERA_WIDTH {     //There is less level then their is camera horizontally
-                        camera_target.x = level_transform.translation.x + (level_width / 2.) - MAX_CAMERA_WIDTH / 2.;
+                    if level_width < camera_size.x {     //There is less level then their is camera horizontally
+                        camera_target.x = level_transform.translation.x + (level_width / 2.) - camera_size.x / 2.;
                     }
                     else {  //There is move level than their is camera horizontally
                         let level_left = level_transform.translation.x;
-                        let level_right = level_left + level.px_wid as f32 - MAX_CAMERA_WIDTH;
+                        let level_right = level_left + level.px_wid as f32 - camera_size.x;
 
-                        camera_target.x = (player_pos.x - MAX_CAMERA_WIDTH / 2.).clamp(level_left, level_right);
+                        camera_target.x = (player_pos.x - camera_size.x / 2.).clamp(level_left, level_right);
+                    }
+
+                    if snap_camera.0 {
+                        camera_transform.translation.x = camera_target.x;
+                        camera_transform.translation.y = camera_target.y;
+                        snap_camera.0 = false;
+                        continue;
                     }
 
                     camera_transform.translation.x += (camera_target.x - camera_transform.translation.x) / 20.;
@@ -438,12 +469,66 @@ fn camera_follow_player(
     }
 }
 
+//Shakes the camera for a moment, like when the player gets hit
+pub struct ScreenShake {
+    pub timer: Timer,
+    pub strength: f32,
+}
+impl Default for ScreenShake {
+    fn default() -> Self {
+        ScreenShake {
+            timer: Timer::from_seconds(0., false),
+            strength: 0.,
+        }
+    }
+}
+
+pub const PLAYER_HIT_SHAKE_TIME: f32 = 0.25;
+pub const PLAYER_HIT_SHAKE_STRENGTH: f32 = 4.;
+
+fn shake_on_player_damage(
+    player_query: Query<Entity, With<Player>>,
+    mut health_event: EventReader<HealthChangeEvent>,
+    mut screen_shake: ResMut<ScreenShake>,
+) {
+    for event in health_event.iter() {
+        if let Some(value) = event.change_type.added() {
+            if value < 0 && player_query.get(event.entity).is_ok() {
+                *screen_shake = ScreenShake {
+                    timer: Timer::from_seconds(PLAYER_HIT_SHAKE_TIME, false),
+                    strength: PLAYER_HIT_SHAKE_STRENGTH,
+                };
+            }
+        }
+    }
+}
+
+//Runs after the camera follows the player, which then smooths the offset back out
+fn apply_screen_shake(
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+    mut screen_shake: ResMut<ScreenShake>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    screen_shake.timer.tick(time.delta());
+    if screen_shake.timer.finished() || !settings.screen_shake {
+        return;
+    }
+
+    let t = time.seconds_since_startup() as f32;
+    let strength = screen_shake.strength * screen_shake.timer.percent_left();
+
+    for mut camera_transform in camera_query.iter_mut() {
+        camera_transform.translation.x += (t * 90.).sin() * strength;
+        camera_transform.translation.y += (t * 73.).cos() * strength;
+    }
+}
 
 //============================================================================
 
 #[derive(Component, Default, Clone)]
 pub struct ParticleTrail {
-    id: i32,
+    _id: i32,
 }
 
 #[derive(Bundle, Default, Clone)]
@@ -452,44 +537,66 @@ pub struct ParticleTrailBundle {
 }
 impl LdtkEntity for ParticleTrailBundle {
     fn bundle_entity(
-        entity_instance: &EntityInstance,
-        layer_instance: &LayerInstance,
+        _entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
         _: Option<&Handle<Image>>,
         _: Option<&TilesetDefinition>,
-        assets: &AssetServer,
-        texture_atlases: &mut Assets<TextureAtlas>,
+        _assets: &AssetServer,
+        _texture_atlases: &mut Assets<TextureAtlas>,
     ) -> Self {
 
-        return ParticleTrailBundle {};
+        ParticleTrailBundle {}
 
     }
 }
 
 //============================================================================
 
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Default)]
 pub enum PlayerPickupType {
+    #[default]
     Coin,
     Gem,
     Boots,
     Axe,
     Knife,
-}
-impl Default for PlayerPickupType {
-    fn default() -> Self {
-        PlayerPickupType::Coin
-    }
+    //Id of the weapon definition to equip
+    Weapon(String),
 }
 impl PlayerPickupType {
-    fn new(value: String) -> Self {
+    pub fn new(value: String, weapon: String) -> Self {
         match value.as_str() {
             "ClimbingAxe"   => { PlayerPickupType::Axe      }
             "Knives"        => { PlayerPickupType::Knife    }
             "Boots"         => { PlayerPickupType::Boots    }
             "Gem"           => { PlayerPickupType::Gem      }
+            "Weapon"        => { PlayerPickupType::Weapon(weapon) }
             _               => { PlayerPickupType::Coin     }
         }
     }
+
+    //The same name used for the item type in LDtk
+    pub fn item_name(&self) -> &str {
+        match self {
+            PlayerPickupType::Axe           => "ClimbingAxe",
+            PlayerPickupType::Knife         => "Knives",
+            PlayerPickupType::Boots         => "Boots",
+            PlayerPickupType::Gem           => "Gem",
+            PlayerPickupType::Coin          => "Coin",
+            PlayerPickupType::Weapon(id)    => id,
+        }
+    }
+
+    pub fn popup(&self) -> Popups {
+        match self {
+            PlayerPickupType::Coin          => Popups::Coin,
+            PlayerPickupType::Gem           => Popups::Gem,
+            PlayerPickupType::Boots         => Popups::Boots,
+            PlayerPickupType::Axe           => Popups::Axe,
+            PlayerPickupType::Knife         => Popups::Knives,
+            PlayerPickupType::Weapon(weapon)=> Popups::Weapon(weapon.clone()),
+        }
+    }
 }
 
 #[derive(Component, Clone, Default)]
@@ -500,6 +607,8 @@ pub struct PickupCollected(pub  bool);
 pub struct PlayerPickupBundle {
     pickup_type: PlayerPickupType,
     collected: PickupCollected,
+    iid: EntityIid,
+    interactable: Interactable,
     #[bundle]
     sprite: SpriteBundle,
     worldly: Worldly,
@@ -510,31 +619,33 @@ impl LdtkEntity for PlayerPickupBundle {
         layer_instance: &LayerInstance,
         _: Option<&Handle<Image>>,
         _: Option<&TilesetDefinition>,
-        assets: &AssetServer,
+        _assets: &AssetServer,
         _: &mut Assets<TextureAtlas>,
     ) -> Self {
 
         let mut item_type = "".to_string();
+        let mut weapon = "".to_string();
         for instance in entity_instance.field_instances.iter() {
             if instance.identifier == "ItemType" {
-                match instance.value.clone() {
-                    FieldValue::String( value) => {
-                        if let Some(value) = value {
-                            item_type = value;
-                        }
-                    },
-                    _ => {}
+                if let FieldValue::String(Some(value)) = instance.value.clone() {
+                    item_type = value;
+                }
+            }
+            if instance.identifier == "Weapon" {
+                if let FieldValue::String(Some(value)) = instance.value.clone() {
+                    weapon = value;
                 }
             }
         }
 
-        let item_type = PlayerPickupType::new(item_type);
-        let sprite_location = match item_type {
-            PlayerPickupType::Coin  => {"Textures/Coin"},
-            PlayerPickupType::Gem   => {"Textures/Gem"},
-            PlayerPickupType::Boots => {"Textures/Boots"},
-            PlayerPickupType::Axe   => {"Textures/Axe"},
-            PlayerPickupType::Knife => {"Textures/Knife"},
+        let item_type = PlayerPickupType::new(item_type, weapon);
+        let _sprite_location = match item_type {
+            PlayerPickupType::Coin      => {"Textures/Coin"},
+            PlayerPickupType::Gem       => {"Textures/Gem"},
+            PlayerPickupType::Boots     => {"Textures/Boots"},
+            PlayerPickupType::Axe       => {"Textures/Axe"},
+            PlayerPickupType::Knife     => {"Textures/Knife"},
+            PlayerPickupType::Weapon(_) => {"Textures/Knife"},
         };
 
         //let sprite_handle = assets.load(sprite_location);
@@ -550,6 +661,8 @@ impl LdtkEntity for PlayerPickupBundle {
                 //texture: sprite_handle,
                 ..Default::default()
             },
+            iid: EntityIid::from_entity_info(entity_instance, layer_instance),
+            interactable: Interactable::new("Pick up", PLAYER_PICKUP_DISTANCE),
             worldly: Worldly::from_entity_info(entity_instance, layer_instance),
             ..Default::default()
         }
@@ -560,71 +673,62 @@ impl LdtkEntity for PlayerPickupBundle {
 pub struct ItemPickedUpEvent(pub PlayerPickupType);
 
 pub fn player_pickup_item(
-    player_query: Query<&GlobalTransform, With<Player>>,
-    mut pickup_query: Query<(&GlobalTransform, &PlayerPickupType, &mut PickupCollected, &mut Visibility), Without<Player>>,
+    mut pickup_query: Query<(&PlayerPickupType, &mut PickupCollected, &mut Visibility)>,
+    mut interact_event: EventReader<InteractEvent>,
     mut pickup_event: EventWriter<ItemPickedUpEvent>,
-    key_input: Res<Input<KeyCode>>,
 
     mut popup: ResMut<Popups>,
     mut popup_state: ResMut<State<ShowPopup>>,
 ) {
 
-    for player_pos in player_query.iter() {
-        for (pickup_pos, pickup_type, mut collected, mut visible) in pickup_query.iter_mut() {
+    for interaction in interact_event.iter() {
+        if let Ok((pickup_type, mut collected, mut visible)) = pickup_query.get_mut(interaction.0) {
             if collected.0 {
                 continue;
             }
 
-            let distance_to_item = player_pos.translation.distance(pickup_pos.translation);
-            if distance_to_item == 0. {
-                continue;
+            pickup_event.send(ItemPickedUpEvent( pickup_type.clone()));
+            collected.0 = true;
+            visible.is_visible = false;
+
+            //Can be queued already by show_pickup_popup on the same frame
+            *popup = pickup_type.popup();
+            if *popup_state.current() != ShowPopup::Show{
+                let _ = popup_state.set(ShowPopup::Show);
             }
-            if distance_to_item < PLAYER_PICKUP_DISTANCE {
+        }
+    }
+}
 
-                if key_input.just_pressed(PLAYER_INTERACT) {
-                    pickup_event.send(ItemPickedUpEvent( pickup_type.clone()));
-                    collected.0 = true;
-                    visible.is_visible = false;
+//Describes the pickup the player is next to before it's picked up
+pub fn show_pickup_popup(
+    pickup_query: Query<(&PlayerPickupType, &PickupCollected)>,
+    target: Res<InteractionTarget>,
 
-                }
+    mut popup: ResMut<Popups>,
+    mut popup_state: ResMut<State<ShowPopup>>,
+) {
+    if let Some((pickup_type, collected)) = target.0.and_then(|entity| pickup_query.get(entity).ok()) {
+        if collected.0 {
+            return;
+        }
 
-                match pickup_type {
-                    PlayerPickupType::Coin => {
-                        *popup = Popups::Coin; 
-                        if *popup_state.current() != ShowPopup::Show{
-                            popup_state.set(ShowPopup::Show).unwrap();
-                        }   
-                    },
-                    PlayerPickupType::Gem => {
-                        *popup = Popups::Gem; 
-                        if *popup_state.current() != ShowPopup::Show{
-                            popup_state.set(ShowPopup::Show).unwrap();
-                        }   
-                    },
-                    PlayerPickupType::Boots => {
-                        *popup = Popups::Boots; 
-                        if *popup_state.current() != ShowPopup::Show{
-                            popup_state.set(ShowPopup::Show).unwrap();
-                        }   
-                    },
-                    PlayerPickupType::Axe => {
-                        *popup = Popups::Axe; 
-                        if *popup_state.current() != ShowPopup::Show{
-                            popup_state.set(ShowPopup::Show).unwrap();
-                        }   
-                    },
-                    PlayerPickupType::Knife => {
-                        *popup = Popups::Knives; 
-                        if *popup_state.current() != ShowPopup::Show{
-                            popup_state.set(ShowPopup::Show).unwrap();
-                        }   
-                    },
-                }
-            }
+        *popup = pickup_type.popup();
+        if *popup_state.current() != ShowPopup::Show {
+            let _ = popup_state.set(ShowPopup::Show);
         }
     }
 }
 
+//Collected pickups (including ones restored from a save) can't be interacted with again
+pub fn disable_collected_pickups(
+    mut pickup_query: Query<(&PickupCollected, &mut Interactable), Changed<PickupCollected>>,
+) {
+    for (collected, mut interactable) in pickup_query.iter_mut() {
+        interactable.enabled = !collected.0;
+    }
+}
+
 //============================================================================
 
 pub fn player_enable_item(
@@ -632,14 +736,28 @@ pub fn player_enable_item(
     mut pickup_event: EventReader<ItemPickedUpEvent>,
     mut commands: Commands,
     assets: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 
     mut coins: ResMut<CoinsCollected>
 ) {
 
+    //Adds the weapon with one of the inventory's add functions, despawning it if there wasn't room
+    let mut equip_weapon = |weapon_id: &str, player: Entity, inventory: &mut WeaponInventory, add: fn(&mut WeaponInventory, Entity) -> bool| {
+
+        let new_weapon = commands.spawn_bundle(WeaponSpawnerBundle::new(weapon_id, &assets, true)).id();
+
+        if add(inventory, new_weapon) {
+            //Weapon added successfully
+            commands.entity(player).add_child(new_weapon);
+        }
+        else {
+            //Weapon was not added
+            commands.entity(new_weapon).despawn();  
+        }
+    };
+
     for event in pickup_event.iter() {
 
-        match event.0 {
+        match &event.0 {
             PlayerPickupType::Coin => {
                 coins.0 += 1;
             },
@@ -657,21 +775,14 @@ pub fn player_enable_item(
                 }
             },
             PlayerPickupType::Knife => {
-
                 for (player, _, _, mut inventory) in player_query.iter_mut() {
-
-                    let new_weapon = commands.spawn_bundle(WeaponBundle::create_throwing_knife(&assets, &mut texture_atlases, true)).id();
-            
-                    if inventory.add_slot1_weapon(new_weapon) {
-                        //Weapon added successfully
-                        commands.entity(player).add_child(new_weapon);
-                    }
-                    else {
-                        //Weapon was not added
-                        commands.entity(new_weapon).despawn();  
-                    }
+                    equip_weapon(THROWING_KNIFE_ID, player, &mut inventory, WeaponInventory::add_slot1_weapon);
+                }
+            },
+            PlayerPickupType::Weapon(weapon_id) => {
+                for (player, _, _, mut inventory) in player_query.iter_mut() {
+                    equip_weapon(weapon_id, player, &mut inventory, WeaponInventory::add_weapon);
                 }
-                
             },
         }
     }
@@ -690,11 +801,11 @@ pub struct EndScreenAreaBundle {
 }
 impl LdtkEntity for EndScreenAreaBundle {
     fn bundle_entity(
-        entity_instance: &EntityInstance,
-        layer_instance: &LayerInstance,
+        _entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
         _: Option<&Handle<Image>>,
         _: Option<&TilesetDefinition>,
-        assets: &AssetServer,
+        _assets: &AssetServer,
         _: &mut Assets<TextureAtlas>,
     ) -> Self { 
 
@@ -713,8 +824,13 @@ fn player_trigger_end_screen(
     mut popup_state: ResMut<State<ShowPopup>>,
 
     mut show_coin_state: ResMut<State<ShowCoinCounter>>,
+    mut game_state: ResMut<State<GameState>>,
+    //Only end the game when the player first walks into range, so they can keep exploring afterwards
+    mut in_range: Local<bool>,
 ) {
 
+    let mut reached_end = false;
+
     for player_pos in player_query.iter() {
         for end_pos in end_trigger_query.iter() {
 
@@ -722,17 +838,202 @@ fn player_trigger_end_screen(
 
 
             if distance_to_item < 200. && distance_to_item != 0. {
-                *popup = Popups::End; 
-                if *popup_state.current() != ShowPopup::Show{
-                    popup_state.set(ShowPopup::Show).unwrap();
-                }  
-                if *show_coin_state.current() != ShowCoinCounter::Show{
-                    show_coin_state.set(ShowCoinCounter::Show).unwrap();
-                }  
+                reached_end = true;
             }
         }
     }
-} 
+
+    if reached_end && !*in_range {
+        *popup = Popups::End; 
+        if *popup_state.current() != ShowPopup::Show{
+            popup_state.set(ShowPopup::Show).unwrap();
+        }  
+        if *show_coin_state.current() != ShowCoinCounter::Show{
+            show_coin_state.set(ShowCoinCounter::Show).unwrap();
+        }  
+        let _ = game_state.set(GameState::Ending);
+    }
+    *in_range = reached_end;
+}
+
+//============================================================================
+
+pub const CHECKPOINT_ACTIVATE_DISTANCE: f32 = 30.;
+
+#[derive(Component, Default, Clone)]
+pub struct Checkpoint;
+
+#[derive(Bundle, Default, Clone)]
+pub struct CheckpointBundle {
+    checkpoint: Checkpoint,
+    iid: EntityIid,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+impl LdtkEntity for CheckpointBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+
+        CheckpointBundle {
+            iid: EntityIid::from_entity_info(entity_instance, layer_instance),
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    color: Color::CYAN,
+                    custom_size: Some(Vec2::new(16., 32.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+//Where the player will respawn after dying
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ActiveCheckpoint {
+    pub level_uid: i32,
+    pub position: Vec2,
+}
+
+pub struct CheckpointActivatedEvent(pub Entity);
+
+//Until a checkpoint has been reached, respawn the player where they first spawned
+fn set_initial_checkpoint(
+    player_query: Query<(&GlobalTransform, &Worldly), Added<Player>>,
+    mut checkpoint: ResMut<Option<ActiveCheckpoint>>,
+) {
+    if checkpoint.is_some() {
+        return;
+    }
+
+    for (transform, worldly) in player_query.iter() {
+        *checkpoint = Some(ActiveCheckpoint {
+            level_uid: worldly.spawn_level,
+            position: transform.translation.truncate(),
+        });
+    }
+}
+
+fn player_activate_checkpoint(
+    mut player_query: Query<(&GlobalTransform, &mut Health), With<Player>>,
+    checkpoint_query: Query<(Entity, &GlobalTransform, &EntityIid), (With<Checkpoint>, Without<Player>)>,
+    mut checkpoint: ResMut<Option<ActiveCheckpoint>>,
+    mut checkpoint_event: EventWriter<CheckpointActivatedEvent>,
+) {
+    for (player_pos, mut health) in player_query.iter_mut() {
+        for (entity, checkpoint_pos, iid) in checkpoint_query.iter() {
+
+            let distance_to_checkpoint = player_pos.translation.distance(checkpoint_pos.translation);
+            if distance_to_checkpoint == 0. || distance_to_checkpoint > CHECKPOINT_ACTIVATE_DISTANCE {
+                continue;
+            }
+
+            let new_checkpoint = ActiveCheckpoint {
+                level_uid: iid.level_uid,
+                position: checkpoint_pos.translation.truncate(),
+            };
+            if *checkpoint == Some(new_checkpoint.clone()) {
+                continue;
+            }
+
+            *checkpoint = Some(new_checkpoint);
+            let max_health = health.get_max_health();
+            health.set_health(max_health);
+            checkpoint_event.send(CheckpointActivatedEvent(entity));
+        }
+    }
+}
+
+//Activated checkpoints flash and are left white
+fn checkpoint_activated_flash(
+    mut checkpoint_event: EventReader<CheckpointActivatedEvent>,
+    mut commands: Commands,
+) {
+    for event in checkpoint_event.iter() {
+        commands.entity(event.0).insert(HealthFlash::new(Color::CYAN, Color::GREEN, 0.3));
+    }
+}
+
+//Sent by the game over menu
+pub struct RetryEvent;
+
+//Puts the player back at the last checkpoint and respawns the loaded levels so enemies
+//and anything else in them start over
+pub fn respawn_player_at_checkpoint(
+    mut player_query: Query<(
+        Entity, &mut Transform, &mut Health, &mut AutoAnimation, &mut TextureAtlasSprite,
+        Option<&mut Velocity>, Option<&mut MaxVelocity>, Option<&StatusEffects>,
+    ), With<Player>>,
+    level_query: Query<Entity, With<Handle<LdtkLevel>>>,
+    projectile_query: Query<Entity, With<Projectile>>,
+    mut retry_event: EventReader<RetryEvent>,
+    checkpoint: Res<Option<ActiveCheckpoint>>,
+    mut current_level: ResMut<LevelSelection>,
+    mut level_changed_event: EventWriter<LevelChangedEvent>,
+    mut change_animation_event: EventWriter<ChangeAnimationEvent>,
+    mut snap_camera: ResMut<SnapCamera>,
+    mut commands: Commands,
+) {
+    if retry_event.iter().count() == 0 {
+        return;
+    }
+
+    for (entity, mut transform, mut health, mut auto_anim, mut sprite, velocity, max_velocity, status_effects) in player_query.iter_mut() {
+
+        let max_health = health.get_max_health();
+        health.set_health(max_health);
+        if let Some(mut velocity) = velocity {
+            velocity.linear = Vec3::ZERO;
+        }
+
+        //Status effects only wear off while playing, so clear them along with their slow and tint
+        let base_max_velocity = status_effects.and_then(|effects| effects.base_max_velocity.clone());
+        if let (Some(mut max_velocity), Some(base_max_velocity)) = (max_velocity, base_max_velocity) {
+            *max_velocity = base_max_velocity;
+        }
+        commands.entity(entity)
+            .remove::<StatusEffects>()
+            .insert(HitStun::default());
+
+        auto_anim.disabled = false;
+        commands.entity(entity).remove::<FadeInOut>();
+        sprite.color = Color::WHITE;
+        change_animation_event.send(ChangeAnimationEvent {
+            entity,
+            new_animation: AnimationType::Idle,
+            restart_animation: true,
+            flipped: FlipAnimation::None,
+        });
+
+        if let Some(checkpoint) = &*checkpoint {
+            transform.translation.x = checkpoint.position.x;
+            transform.translation.y = checkpoint.position.y;
+
+            if *current_level != LevelSelection::Uid(checkpoint.level_uid) {
+                level_changed_event.send(LevelChangedEvent(checkpoint.level_uid));
+            }
+            *current_level = LevelSelection::Uid(checkpoint.level_uid);
+        }
+    }
+
+    //Even if the level is the same, marking it changed makes bevy_ecs_ldtk spawn the
+    //despawned levels again next frame
+    current_level.set_changed();
+    for level in level_query.iter() {
+        commands.entity(level).despawn_recursive();
+    }
+    for projectile in projectile_query.iter() {
+        commands.entity(projectile).despawn_recursive();
+    }
+    snap_camera.0 = true;
+}
 
 //============================================================================
 
@@ -741,6 +1042,8 @@ impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app
             .insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.1)))
+            .insert_resource(SnapCamera(false))
+            .insert_resource(LevelWallGrids::default())
             .add_event::<LevelChangedEvent>()
 
             .register_ldtk_int_cell_for_layer::<WallBundle>("Tiles", 1)
@@ -749,18 +1052,38 @@ impl Plugin for WorldPlugin {
             
             .register_ldtk_entity::<PlayerPickupBundle>("ItemPickup")
             .register_ldtk_entity::<EndScreenAreaBundle>("Ending")
+            .register_ldtk_entity::<CheckpointBundle>("Checkpoint")
 
             .register_ldtk_entity::<ParticleTrailBundle>("ParticleTrail")
 
             .add_system(spawn_wall_collision)
-            .add_system(change_level)
-            .add_system(camera_follow_player)
+            .add_system(camera_follow_player.label("CameraFollow"))
+            .insert_resource(ScreenShake::default())
+            .add_system(apply_screen_shake.after("CameraFollow"))
             .add_system(set_fog_of_war)
 
             .add_event::<ItemPickedUpEvent>()
-            .add_system(player_pickup_item)
-            .add_system(player_enable_item)
-            .add_system(player_trigger_end_screen)
+
+            .insert_resource::<Option<ActiveCheckpoint>>(None)
+            .add_event::<CheckpointActivatedEvent>()
+            .add_event::<RetryEvent>()
+            //Retrying happens from the game over menu
+            .add_system(respawn_player_at_checkpoint)
+
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(disable_collected_pickups)
+                    .with_system(set_initial_checkpoint)
+                    .with_system(checkpoint_activated_flash)
+
+                    .with_system(change_level)
+                    .with_system(player_pickup_item)
+                    .with_system(show_pickup_popup)
+                    .with_system(player_enable_item)
+                    .with_system(player_trigger_end_screen)
+                    .with_system(player_activate_checkpoint)
+                    .with_system(shake_on_player_damage)
+            )
         ;
     }
 }
@@ -0,0 +1,592 @@
+//===============================================================
+
+use std::collections::HashMap;
+
+use bevy::{prelude::*, app::Events};
+use heron::Velocity;
+
+use metroidvania_game_jam_v2::{
+    simulation::*,
+    controls::controls_components::PlayerAction,
+    physics::physics_components::{IsGrounded, MaxVelocity},
+    general::general_components::{
+        Health, HealthChangeEvent, HealthChangeType, Knockback, HitStun,
+        Damage, DamageType, Resistances, StatusEffect, StatusEffects, HIT_STUN_TIME, EntityDiedEvent,
+    },
+    player::player_components::{PLAYER_MAX_HEALTH, PLAYER_MAX_SPEED, PLAYER_MAX_SPRINT_SPEED, PlayerSprint},
+    weapons::weapon_components::{WeaponInventory, WeaponState, FireWeaponEvent},
+    replay::replay_components::{InputRecording, REPLAY_TIMESTEP},
+    save::save_components::SaveData,
+    general::tools::{load_ron, save_ron},
+    weapons::weapon_prefabs::{SWORD_ID, THROWING_KNIFE_ID},
+    boss::boss_components::Boss,
+    progression::progression_components::ProgressFlags,
+    world::{ItemPickedUpEvent, PlayerPickupType, LevelChangedEvent, CheckpointActivatedEvent, ActiveCheckpoint, RetryEvent},
+    general::GameState,
+    non_player::{
+        non_player_definitions::EnemyRespawn, non_player_prefabs::EnemySpawner,
+        non_player_components::{DefeatedEnemies, NonPlayerAggressiveState},
+    },
+    transition::transition_components::{Transition, TransitionState, TransitionTarget},
+    interaction::interaction_components::{Interactable, InteractEvent},
+    controls::controls_components::ActionInput,
+};
+use bevy_ecs_ldtk::prelude::{LevelSelection, LdtkAsset, LdtkLevel};
+
+//===============================================================
+
+fn app_with_player_on_ground() -> (App, Entity) {
+    let mut app = headless_app();
+
+    //Walls are spawned after the player, the same as when a level loads.
+    //Contact normals depend on which of the two was added to physics first
+    let player = spawn_player(&mut app, Vec2::new(0., 40.));
+    spawn_wall(&mut app, Vec2::new(0., 0.), Vec2::new(400., 16.));
+
+    step_frames(&mut app, 120);
+
+    (app, player)
+}
+
+fn kill(app: &mut App, entity: Entity) {
+    app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap().send(HealthChangeEvent {
+        entity,
+        change_type: HealthChangeType::Set { value: 0 },
+        knockback: None,
+    });
+    step_frames(app, 1);
+}
+
+//Sets the held actions every frame, the same as a device that keeps reporting them
+fn hold_actions(app: &mut App, actions: &[PlayerAction], frames: usize) {
+    for _ in 0..frames {
+        set_actions(app, actions);
+        app.update();
+    }
+}
+
+fn enemy_count(app: &mut App) -> usize {
+    app.world.query::<&EnemySpawner>().iter(&app.world).count()
+}
+
+//Kills an enemy in level 1 and checks it was recorded as defeated
+fn app_with_defeated_enemy(name: &str, respawn: Option<EnemyRespawn>) -> App {
+    let (mut app, _) = app_with_player_on_ground();
+
+    let enemy = spawn_enemy(&mut app, name, Vec2::new(300., 200.), 1, respawn);
+    step_frames(&mut app, 1);
+    kill(&mut app, enemy);
+
+    assert!(app.world.get_entity(enemy).is_none());
+    assert_eq!(enemy_count(&mut app), 0);
+    app
+}
+
+//===============================================================
+
+#[test]
+fn player_lands_on_ground() {
+    let (app, player) = app_with_player_on_ground();
+
+    let grounded = app.world.get::<IsGrounded>(player).unwrap();
+    assert!(grounded.grounded);
+}
+
+#[test]
+fn player_moves_right() {
+    let (mut app, player) = app_with_player_on_ground();
+
+    let start = app.world.get::<Transform>(player).unwrap().translation;
+
+    set_actions(&mut app, &[PlayerAction::MoveRight]);
+    step_frames(&mut app, 30);
+
+    //Speeds up by 400/60 a frame until it hits 120 on frame 18, then holds it for 12 frames.
+    //(400/60 * (1 + .. + 18) + 120 * 12) / 60 = 43
+    let end = app.world.get::<Transform>(player).unwrap().translation;
+    assert_eq!(end.x - start.x, 43.);
+    assert_eq!(end.y, start.y);
+}
+
+#[test]
+fn player_takes_damage() {
+    let (mut app, player) = app_with_player_on_ground();
+
+    app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap().send(HealthChangeEvent {
+        entity: player,
+        change_type: HealthChangeType::Add { value: -10 },
+        knockback: None,
+    });
+    step_frames(&mut app, 1);
+
+    let health = app.world.get::<Health>(player).unwrap();
+    assert_eq!(health.get_health(), PLAYER_MAX_HEALTH - 10);
+}
+
+#[test]
+fn damage_knocks_player_away_from_source() {
+    let (mut app, player) = app_with_player_on_ground();
+
+    let start_x = app.world.get::<Transform>(player).unwrap().translation.x;
+
+    //Held against the knockback, which has to win while the player is stunned
+    set_actions(&mut app, &[PlayerAction::MoveLeft]);
+    app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap().send(HealthChangeEvent {
+        entity: player,
+        change_type: HealthChangeType::Add { value: -10 },
+        knockback: Some(Knockback {
+            source: Vec2::new(start_x - 20., 40.),
+            force: 300.,
+        }),
+    });
+    step_frames(&mut app, 1);
+    assert!(app.world.get::<HitStun>(player).unwrap().is_stunned());
+
+    step_frames(&mut app, 10);
+    let end_x = app.world.get::<Transform>(player).unwrap().translation.x;
+    assert!(end_x > start_x);
+
+    let stun_frames = (HIT_STUN_TIME as f64 / SIMULATION_TIMESTEP).ceil() as usize;
+    step_frames(&mut app, stun_frames - 10);
+    assert!(!app.world.get::<HitStun>(player).unwrap().is_stunned());
+}
+
+#[test]
+fn input_is_ignored_on_the_frame_of_a_hit() {
+    let hit_velocity = |actions: &[PlayerAction]| {
+        let (mut app, player) = app_with_player_on_ground();
+        let x = app.world.get::<Transform>(player).unwrap().translation.x;
+
+        set_actions(&mut app, actions);
+        app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap().send(HealthChangeEvent {
+            entity: player,
+            change_type: HealthChangeType::Add { value: -10 },
+            knockback: Some(Knockback {
+                source: Vec2::new(x - 20., 40.),
+                force: 300.,
+            }),
+        });
+        step_frames(&mut app, 1);
+
+        app.world.get::<Velocity>(player).unwrap().linear
+    };
+
+    assert_eq!(hit_velocity(&[PlayerAction::MoveLeft]), hit_velocity(&[]));
+}
+
+#[test]
+fn resistances_reduce_damage_and_block_status() {
+    let (mut app, player) = app_with_player_on_ground();
+
+    app.world.entity_mut(player).insert(Resistances(HashMap::from([(DamageType::Fire, 1.)])));
+
+    app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap().send(HealthChangeEvent {
+        entity: player,
+        change_type: HealthChangeType::Damage(Damage {
+            amount: 10,
+            damage_type: DamageType::Fire,
+            status: Some(StatusEffect::Burn { damage: 2, duration: 1. }),
+        }),
+        knockback: None,
+    });
+    step_frames(&mut app, 1);
+
+    let health = app.world.get::<Health>(player).unwrap();
+    assert_eq!(health.get_health(), PLAYER_MAX_HEALTH);
+    assert!(app.world.get::<StatusEffects>(player).is_none());
+}
+
+#[test]
+fn slow_reduces_max_speed_until_it_wears_off() {
+    let (mut app, player) = app_with_player_on_ground();
+
+    app.world.entity_mut(player).insert(Resistances(HashMap::from([(DamageType::Physical, 0.5)])));
+
+    app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap().send(HealthChangeEvent {
+        entity: player,
+        change_type: HealthChangeType::Damage(Damage {
+            amount: 10,
+            damage_type: DamageType::Physical,
+            status: Some(StatusEffect::Slow { amount: 0.5, duration: 0.5 }),
+        }),
+        knockback: None,
+    });
+    step_frames(&mut app, 2);
+
+    assert_eq!(app.world.get::<Health>(player).unwrap().get_health(), PLAYER_MAX_HEALTH - 5);
+    assert!(app.world.get::<StatusEffects>(player).is_some());
+    assert_eq!(app.world.get::<MaxVelocity>(player).unwrap().x, PLAYER_MAX_SPEED * 0.5);
+
+    step_frames(&mut app, 40);
+    assert!(app.world.get::<StatusEffects>(player).is_none());
+    assert_eq!(app.world.get::<MaxVelocity>(player).unwrap().x, PLAYER_MAX_SPEED);
+}
+
+#[test]
+fn poison_drains_health_until_it_kills() {
+    let (mut app, player) = app_with_player_on_ground();
+
+    let mut died_reader = app.world.get_resource::<Events<EntityDiedEvent>>().unwrap().get_reader();
+
+    let mut health_events = app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap();
+    health_events.send(HealthChangeEvent {
+        entity: player,
+        change_type: HealthChangeType::Set { value: 3 },
+        knockback: None,
+    });
+    health_events.send(HealthChangeEvent {
+        entity: player,
+        change_type: HealthChangeType::Damage(Damage {
+            amount: 0,
+            damage_type: DamageType::Poison,
+            status: Some(StatusEffect::Poison { damage: 2, duration: 2. }),
+        }),
+        knockback: None,
+    });
+
+    //Ticks every STATUS_DAMAGE_TICK, which is 30 frames
+    step_frames(&mut app, 31);
+    assert_eq!(app.world.get::<Health>(player).unwrap().get_health(), 1);
+
+    step_frames(&mut app, 30);
+    let died = died_reader.iter(app.world.get_resource::<Events<EntityDiedEvent>>().unwrap()).count();
+    assert_eq!(died, 1);
+}
+
+#[test]
+fn replay_ends_in_the_same_place() {
+    let mut recording = InputRecording {
+        timestep: REPLAY_TIMESTEP,
+        ..Default::default()
+    };
+    recording.frames.extend(std::iter::repeat_n(vec![PlayerAction::MoveRight], 20));
+    recording.frames.extend(std::iter::repeat_n(vec![PlayerAction::MoveRight, PlayerAction::Jump], 15));
+    recording.frames.extend(std::iter::repeat_n(vec![PlayerAction::MoveLeft], 30));
+
+    let replay = |recording: &InputRecording| {
+        let mut app = headless_app();
+        set_timestep(&mut app, recording.timestep);
+
+        let player = spawn_player(&mut app, Vec2::new(0., 40.));
+        spawn_wall(&mut app, Vec2::new(0., 0.), Vec2::new(400., 16.));
+        step_frames(&mut app, 120);
+
+        replay_recording(&mut app, recording, player)
+    };
+
+    let recorded = replay(&recording);
+    recording.final_player_position = Some((recorded.x, recorded.y));
+
+    let replayed = replay(&recording);
+    assert_eq!(recorded, replayed);
+    assert!(recording.matches(Some(replayed)));
+    assert!(!recording.matches(Some(replayed + Vec2::new(16., 0.))));
+}
+
+#[test]
+fn primary_attack_charges_slot1_weapon() {
+    let (mut app, player) = app_with_player_on_ground();
+
+    let weapon = app.world.spawn().insert(WeaponState::default()).id();
+    let added = app.world.get_mut::<WeaponInventory>(player).unwrap().add_weapon(weapon);
+    assert!(added);
+    assert_eq!(*app.world.get::<WeaponInventory>(player).unwrap().get_slot1(), Some(weapon));
+
+    set_actions(&mut app, &[PlayerAction::PrimaryAttack]);
+    step_frames(&mut app, 1);
+    assert!(app.world.get::<WeaponState>(weapon).unwrap().charging);
+
+    set_actions(&mut app, &[]);
+    step_frames(&mut app, 1);
+    assert!(!app.world.get::<WeaponState>(weapon).unwrap().charging);
+}
+
+#[test]
+fn old_save_weapon_ids_are_migrated() {
+    let path = std::env::temp_dir().join("old_weapon_ids.save.ron");
+    let path = path.to_str().unwrap();
+
+    save_ron(&SaveData {
+        weapon_slot1: Some("ThrowingKnife".to_string()),
+        weapon_slot2: Some("Sword".to_string()),
+        ..Default::default()
+    }, path);
+
+    let mut save: SaveData = load_ron(path).unwrap();
+    save.migrate();
+    assert_eq!(save.weapon_slot1.as_deref(), Some(THROWING_KNIFE_ID));
+    assert_eq!(save.weapon_slot2.as_deref(), Some(SWORD_ID));
+}
+
+#[test]
+fn boss_phase_starts_at_its_health_threshold() {
+    let (mut app, _) = app_with_player_on_ground();
+
+    //Out of the player's level, so it doesn't attack
+    let boss = spawn_enemy(&mut app, "batqueen", Vec2::new(300., 200.), 1, None);
+    step_frames(&mut app, 1);
+    assert_eq!(app.world.get::<Boss>(boss).unwrap().phase, 0);
+
+    //Its only extra phase starts at half of 150 health
+    app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap().send(HealthChangeEvent {
+        entity: boss,
+        change_type: HealthChangeType::Set { value: 76 },
+        knockback: None,
+    });
+    step_frames(&mut app, 2);
+    assert_eq!(app.world.get::<Boss>(boss).unwrap().phase, 0);
+
+    app.world.get_resource_mut::<Events<HealthChangeEvent>>().unwrap().send(HealthChangeEvent {
+        entity: boss,
+        change_type: HealthChangeType::Set { value: 75 },
+        knockback: None,
+    });
+    step_frames(&mut app, 2);
+    assert_eq!(app.world.get::<Boss>(boss).unwrap().phase, 1);
+}
+
+#[test]
+fn defeated_boss_sets_its_flag_and_gives_its_reward() {
+    let (mut app, _) = app_with_player_on_ground();
+
+    let boss = spawn_enemy(&mut app, "batqueen", Vec2::new(300., 200.), 1, None);
+    step_frames(&mut app, 1);
+    assert!(app.world.get::<Boss>(boss).is_some());
+
+    let mut pickup_reader = app.world.get_resource::<Events<ItemPickedUpEvent>>().unwrap().get_reader();
+
+    kill(&mut app, boss);
+
+    //The boss is gone by the end of the frame it died on, so this only works if it was seen dying first
+    assert!(app.world.get_entity(boss).is_none());
+    assert!(app.world.get_resource::<ProgressFlags>().unwrap().contains(&ProgressFlags::boss("Bat Queen")));
+
+    let pickups = app.world.get_resource::<Events<ItemPickedUpEvent>>().unwrap();
+    let rewards: Vec<_> = pickup_reader.iter(pickups).collect();
+    assert_eq!(rewards.len(), 1);
+    assert!(matches!(rewards[0].0, PlayerPickupType::Gem));
+}
+
+#[test]
+fn room_entry_enemy_respawns_once_another_room_is_entered() {
+    let mut app = app_with_defeated_enemy("bat", None);
+
+    app.world.get_resource_mut::<Events<LevelChangedEvent>>().unwrap().send(LevelChangedEvent(1));
+    step_frames(&mut app, 2);
+    assert_eq!(enemy_count(&mut app), 0);
+
+    app.world.get_resource_mut::<Events<LevelChangedEvent>>().unwrap().send(LevelChangedEvent(2));
+    step_frames(&mut app, 2);
+    assert_eq!(enemy_count(&mut app), 1);
+}
+
+#[test]
+fn rest_enemy_respawns_only_at_a_checkpoint() {
+    let mut app = app_with_defeated_enemy("fox", None);
+
+    app.world.get_resource_mut::<Events<LevelChangedEvent>>().unwrap().send(LevelChangedEvent(2));
+    step_frames(&mut app, 2);
+    assert_eq!(enemy_count(&mut app), 0);
+
+    let checkpoint = app.world.spawn().id();
+    app.world.get_resource_mut::<Events<CheckpointActivatedEvent>>().unwrap().send(CheckpointActivatedEvent(checkpoint));
+    step_frames(&mut app, 2);
+    assert_eq!(enemy_count(&mut app), 1);
+}
+
+#[test]
+fn never_enemy_stays_defeated() {
+    //The Respawn field in LDtk overrides the definition
+    let mut app = app_with_defeated_enemy("fox", Some(EnemyRespawn::Never));
+
+    app.world.get_resource_mut::<Events<LevelChangedEvent>>().unwrap().send(LevelChangedEvent(2));
+    let checkpoint = app.world.spawn().id();
+    app.world.get_resource_mut::<Events<CheckpointActivatedEvent>>().unwrap().send(CheckpointActivatedEvent(checkpoint));
+    step_frames(&mut app, 2);
+    assert_eq!(enemy_count(&mut app), 0);
+
+    let defeated = app.world.get_resource::<DefeatedEnemies>().unwrap();
+    assert_eq!(defeated.permanent.len(), 1);
+    assert!(defeated.respawning.is_empty());
+}
+
+#[test]
+fn edge_transition_fades_out_moves_the_player_and_fades_in() {
+    let mut app = headless_app();
+    let world = load_world(&mut app, "Tilemaps/TileMapTheNew.ldtk");
+    let target = TransitionTarget::find(
+        app.world.get_resource::<Assets<LdtkAsset>>().unwrap().get(&world).unwrap(),
+        "Level_0",
+        "village",
+    ).expect("Level_0 is missing the village EntryPoint");
+
+    let player = spawn_player(&mut app, Vec2::new(5000., 5000.));
+    app.world
+        .spawn()
+        .insert(Transition {
+            target_level: "Level_0".to_string(),
+            entry_point: "village".to_string(),
+            size: Vec2::new(64., 64.),
+            is_door: false,
+        })
+        .insert(Transform::from_xyz(5000., 5000., 0.))
+        .insert(GlobalTransform::from_xyz(5000., 5000., 0.));
+
+    let mut states = Vec::new();
+    let mut arrived_at = None;
+    for _ in 0..60 {
+        step_frames(&mut app, 1);
+        let state = match *app.world.get_resource::<TransitionState>().unwrap() {
+            TransitionState::Idle { armed } => format!("Idle(armed={})", armed),
+            TransitionState::FadingOut { .. } => "FadingOut".to_string(),
+            TransitionState::FadingIn { .. } => "FadingIn".to_string(),
+        };
+        if states.last() != Some(&state) {
+            //Moved while the screen is black. Without any walls here the player starts falling straight after
+            if state == "FadingIn" {
+                arrived_at = Some(app.world.get::<Transform>(player).unwrap().translation.truncate());
+            }
+            states.push(state);
+        }
+    }
+
+    //Fires once the player's position has been picked up, and can fire again once they're out of the edge
+    assert_eq!(states, ["Idle(armed=true)", "FadingOut", "FadingIn", "Idle(armed=false)", "Idle(armed=true)"]);
+    assert_eq!(*app.world.get_resource::<LevelSelection>().unwrap(), LevelSelection::Uid(target.level_uid));
+
+    let position = arrived_at.unwrap();
+    assert!(position.distance(target.position) < 16., "Player is at {} instead of {}", position, target.position);
+}
+
+#[test]
+fn interacting_uses_up_the_shared_sprint_button() {
+    let (mut app, player) = app_with_player_on_ground();
+    app.world.get_mut::<PlayerSprint>(player).unwrap().can_sprint = true;
+
+    //Both are on Z by default
+    let z = [PlayerAction::Interact, PlayerAction::Sprint];
+
+    //With nothing to interact with, Z sprints
+    hold_actions(&mut app, &z, 5);
+    assert_eq!(app.world.get::<MaxVelocity>(player).unwrap().x, PLAYER_MAX_SPRINT_SPEED);
+    hold_actions(&mut app, &[], 5);
+    assert_eq!(app.world.get::<MaxVelocity>(player).unwrap().x, PLAYER_MAX_SPEED);
+
+    let position = app.world.get::<GlobalTransform>(player).unwrap().translation;
+    let switch = app.world
+        .spawn()
+        .insert(Interactable::new("Pull switch", 30.))
+        .insert(Transform::from_translation(position))
+        .insert(GlobalTransform::from_translation(position))
+        .id();
+    step_frames(&mut app, 1);
+
+    let mut interact_reader = app.world.get_resource::<Events<InteractEvent>>().unwrap().get_reader();
+    let mut interactions = |app: &App| -> Vec<Entity> {
+        interact_reader
+            .iter(app.world.get_resource::<Events<InteractEvent>>().unwrap())
+            .map(|event| event.0)
+            .collect()
+    };
+
+    //Only the press interacts, and sprint stays off for as long as Z is held
+    hold_actions(&mut app, &z, 1);
+    assert_eq!(interactions(&app), [switch]);
+    hold_actions(&mut app, &z, 5);
+    assert!(interactions(&app).is_empty());
+    assert!(!app.world.get_resource::<ActionInput>().unwrap().pressed(PlayerAction::Sprint));
+    assert_eq!(app.world.get::<MaxVelocity>(player).unwrap().x, PLAYER_MAX_SPEED);
+}
+
+//Puts a bat in the player's room, well within its attack range, and lets it look around
+fn bat_notices_player(wall_in_between: bool) -> bool {
+    let (mut app, _) = app_with_player_on_ground();
+    *app.world.get_resource_mut::<LevelSelection>().unwrap() = LevelSelection::Uid(1);
+
+    if wall_in_between {
+        spawn_wall(&mut app, Vec2::new(60., 80.), Vec2::new(16., 160.));
+        step_frames(&mut app, 1);
+    }
+
+    let bat = spawn_enemy(&mut app, "bat", Vec2::new(120., 48.), 1, None);
+    step_frames(&mut app, 10);
+
+    matches!(app.world.get::<NonPlayerAggressiveState>(bat), Some(NonPlayerAggressiveState::Attack { .. }))
+}
+
+#[test]
+fn enemies_cant_see_through_walls() {
+    assert!(bat_notices_player(false));
+    assert!(!bat_notices_player(true));
+}
+
+#[test]
+fn enemy_weapon_fires_after_its_cooldown_and_charge() {
+    //No player, so the bat keeps attacking whatever it's told to
+    let mut app = headless_app();
+    let target = app.world
+        .spawn()
+        .insert(Transform::from_xyz(100., 0., 0.))
+        .insert(GlobalTransform::from_xyz(100., 0., 0.))
+        .id();
+    let bat = spawn_enemy(&mut app, "bat", Vec2::ZERO, 1, None);
+    step_frames(&mut app, 1);
+
+    //Weapons are built once their definition has loaded on another thread
+    let knife = app.world.get::<WeaponInventory>(bat).unwrap().get_slot1().unwrap();
+    for _ in 0..600 {
+        if app.world.get::<WeaponState>(knife).is_some() {
+            break;
+        }
+        step_frames(&mut app, 1);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert!(app.world.get::<WeaponState>(knife).is_some(), "Throwing knife never loaded");
+
+    app.world.entity_mut(bat).insert(NonPlayerAggressiveState::Attack { target });
+
+    //The bat's knife cools down for 3 seconds, then charges for 0.8
+    let mut fired_on = Vec::new();
+    let mut fire_reader = app.world.get_resource::<Events<FireWeaponEvent>>().unwrap().get_reader();
+    for frame in 1..=480 {
+        step_frames(&mut app, 1);
+        let events = app.world.get_resource::<Events<FireWeaponEvent>>().unwrap();
+        if fire_reader.iter(events).any(|event| event.0 == knife) {
+            fired_on.push(frame);
+        }
+    }
+
+    assert_eq!(fired_on.len(), 2, "Fired on frames {:?}", fired_on);
+    assert!((226..=230).contains(&fired_on[0]), "Fired on frames {:?}", fired_on);
+    assert!((226..=230).contains(&(fired_on[1] - fired_on[0])), "Fired on frames {:?}", fired_on);
+}
+
+#[test]
+fn retry_restores_the_player_and_reloads_the_levels() {
+    let (mut app, player) = app_with_player_on_ground();
+    let level = app.world.spawn().insert(Handle::<LdtkLevel>::default()).id();
+    *app.world.get_resource_mut::<Option<ActiveCheckpoint>>().unwrap() = Some(ActiveCheckpoint {
+        level_uid: 3,
+        position: Vec2::new(150., 40.),
+    });
+
+    kill(&mut app, player);
+    step_frames(&mut app, 1);
+    assert_eq!(*app.world.get_resource::<State<GameState>>().unwrap().current(), GameState::Dead);
+
+    //The same as the game over menu
+    app.world.get_resource_mut::<Events<RetryEvent>>().unwrap().send(RetryEvent);
+    app.world.get_resource_mut::<State<GameState>>().unwrap().set(GameState::Playing).unwrap();
+    step_frames(&mut app, 1);
+
+    assert_eq!(*app.world.get_resource::<State<GameState>>().unwrap().current(), GameState::Playing);
+    assert_eq!(app.world.get::<Health>(player).unwrap().get_health(), PLAYER_MAX_HEALTH);
+    assert_eq!(*app.world.get_resource::<LevelSelection>().unwrap(), LevelSelection::Uid(3));
+    //Despawned so bevy_ecs_ldtk spawns it again with everything in it
+    assert!(app.world.get_entity(level).is_none());
+
+    let position = app.world.get::<Transform>(player).unwrap().translation.truncate();
+    assert!(position.distance(Vec2::new(150., 40.)) < 2., "Player is at {}", position);
+}
+
+//===============================================================